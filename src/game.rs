@@ -1,38 +1,94 @@
+pub mod config;
 pub mod cursor;
+pub mod free_for_all;
+pub mod net;
 pub mod player_board;
 pub mod players;
 pub mod point;
+pub mod record;
+pub mod rng;
 mod rotation;
+pub mod save;
+pub mod series;
 pub mod ship;
 mod tile;
 pub mod ui;
+pub mod weapon;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::thread::sleep;
 use std::time::Duration;
 
-use ratatui::text::Text;
+use rand::Rng;
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
 
-use crate::game::players::{GamePlayer, Player};
-use crate::game::ship::ShipBlueprint;
+use crate::game::config::{FirstPlayer, GameConfig};
+use crate::game::net::commitment::TamperError;
+use crate::game::player_board::ShotOutcome;
+use crate::game::players::{Action, GamePlayer, Player};
+use crate::game::point::Point;
+use crate::game::record::{GameRecord, RecordedPlayer};
+use crate::game::save::SavedGame;
+use crate::game::series::SeriesScore;
+use crate::game::ship::Maneuver;
 use crate::game::ui::WaitForKey;
+use crate::game::weapon::{Arsenal, Strike, Weapon};
 
 pub struct GameResult {
-    pub winner: CurrentPlayer,
-    pub winner_name: String,
+    // None when both fleets went down in the same round of simultaneous turns
+    pub winner: Option<CurrentPlayer>,
+    pub winner_name: Option<String>,
+    // the player whose answers turned out to be lies, with what gave them away
+    pub tampered: Option<(String, TamperError)>,
+    // the player who left before the game was over, with why
+    pub forfeit: Option<(String, String)>,
+    pub record: GameRecord,
+    // how the series stands when this game was part of one
+    pub series: Option<SeriesScore>,
+}
+// how a game stopped
+pub enum GameEnd {
+    Over(Box<GameResult>),
+    // a player quit, with the game to continue later unless one of the players can't be saved
+    Quit(Option<Box<SavedGame>>),
 }
 pub trait GameMode {
-    fn run(self) -> GameResult;
+    // plays one game, the mode can be set up and played again afterwards
+    fn play(&mut self) -> GameEnd;
+    fn run(mut self) -> GameEnd
+    where
+        Self: Sized,
+    {
+        self.play()
+    }
 }
 pub trait Setup<T> {
     fn setup(&mut self, arg: T);
 }
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CurrentPlayer {
     First = 1,
     Second = 2,
 }
+impl CurrentPlayer {
+    pub fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+}
+
+enum Turn {
+    Done,
+    // the shot earned another one under the extra turn rule
+    Again,
+    // the shot was invalid, the same player goes again
+    Retry,
+    End(GameEnd),
+}
 
 pub struct SinglePlayer<T, U>
 where
@@ -42,6 +98,10 @@ where
     player1: T,
     player2: U,
     current_player: CurrentPlayer,
+    config: GameConfig,
+    record: GameRecord,
+    // the special attack charges player 1 and player 2 have left
+    arsenals: [Arsenal; 2],
 }
 
 impl<T, U> SinglePlayer<T, U>
@@ -54,10 +114,38 @@ where
             player1,
             player2,
             current_player: CurrentPlayer::First,
+            record: GameRecord::new(&GameConfig::default()),
+            config: GameConfig::default(),
+            arsenals: Default::default(),
         }
     }
+    // picks a saved game up where it was left, the players are already set up
+    pub fn resume(
+        player1: T,
+        player2: U,
+        config: GameConfig,
+        current: CurrentPlayer,
+        record: GameRecord,
+    ) -> Self {
+        Self {
+            player1,
+            player2,
+            current_player: current,
+            arsenals: [config.rules.weapons; 2],
+            config,
+            record,
+        }
+    }
+    // the charges each player had left when the game was saved
+    pub fn with_arsenals(mut self, arsenals: [Arsenal; 2]) -> Self {
+        self.arsenals = arsenals;
+        self
+    }
     fn current(&self) -> &dyn GamePlayer {
-        match self.current_player {
+        self.player(self.current_player)
+    }
+    fn player(&self, player: CurrentPlayer) -> &dyn GamePlayer {
+        match player {
             CurrentPlayer::First => &self.player1,
             CurrentPlayer::Second => &self.player2,
         }
@@ -69,6 +157,12 @@ where
     //     }
     // }
 
+    fn player_mut(&mut self, player: CurrentPlayer) -> &mut dyn GamePlayer {
+        match player {
+            CurrentPlayer::First => &mut self.player1,
+            CurrentPlayer::Second => &mut self.player2,
+        }
+    }
     // returns (current_player, opponent_player)
     fn players_mut(&mut self) -> (&mut dyn GamePlayer, &mut dyn GamePlayer) {
         match self.current_player {
@@ -77,53 +171,392 @@ where
         }
     }
     pub fn switch(&mut self) {
-        self.current_player = match self.current_player {
-            CurrentPlayer::First => CurrentPlayer::Second,
-            CurrentPlayer::Second => CurrentPlayer::First,
-        }
+        self.current_player = self.current_player.other();
     }
     pub fn is_game_over(&self) -> bool {
         self.player1.is_game_over() || self.player2.is_game_over()
     }
+    // the player who won, a blast from a mine can sink the shooter's own last ship, None for a
+    // draw when both fleets are gone
+    fn winner(&self) -> Option<CurrentPlayer> {
+        match (self.player1.is_game_over(), self.player2.is_game_over()) {
+            (true, false) => Some(CurrentPlayer::Second),
+            (false, true) => Some(CurrentPlayer::First),
+            _ => None,
+        }
+    }
+    // the player who left the game, first come first served
+    fn left(&self) -> Option<CurrentPlayer> {
+        [CurrentPlayer::First, CurrentPlayer::Second]
+            .into_iter()
+            .find(|&player| self.player(player).gone().is_some())
+    }
+    // the player gave the game up, the other one wins without the fleets being checked, a player
+    // who is gone can't show theirs
+    fn forfeit(&mut self, player: CurrentPlayer) -> GameEnd {
+        let name = self.player(player).get_name().clone();
+        // one who was caught lying is shown as the cheater they are rather than as having left
+        let (tampered, forfeit) = match self.player_mut(player).caught() {
+            Some(err) => (Some((name, err)), None),
+            None => {
+                let why = self.player(player).gone();
+                (
+                    None,
+                    Some((name, why.unwrap_or_else(|| String::from("they gave up")))),
+                )
+            }
+        };
+        let winner = player.other();
+        self.record.winner = Some(winner);
+        GameEnd::Over(Box::new(GameResult {
+            winner: Some(winner),
+            winner_name: Some(self.player(winner).get_name().clone()),
+            tampered,
+            forfeit,
+            record: self.record.clone(),
+            series: None,
+        }))
+    }
+    // the game is over, check that both players played fair
+    fn result(&mut self) -> GameResult {
+        let tampered = match (self.player1.verify(), self.player2.verify()) {
+            (Err(err), _) => Some((self.player1.get_name().clone(), err)),
+            (_, Err(err)) => Some((self.player2.get_name().clone(), err)),
+            _ => None,
+        };
+        // so are the moves of those fleets
+        for (player, moves) in [
+            (CurrentPlayer::First, self.player1.moves()),
+            (CurrentPlayer::Second, self.player2.moves()),
+        ] {
+            if let Some(moves) = moves {
+                self.record.reveal_moves(player, &moves);
+            }
+        }
+        // fleets that were hidden during the game are known once it's over
+        for (player, fleet) in [
+            (CurrentPlayer::First, self.player1.fleet()),
+            (CurrentPlayer::Second, self.player2.fleet()),
+        ] {
+            let recorded = self.record.player_mut(player);
+            if recorded.layout.is_none() {
+                recorded.layout = fleet;
+            }
+        }
+        let winner = self.winner();
+        self.record.winner = winner;
+        let winner_name = winner.map(|winner| match winner {
+            CurrentPlayer::First => self.player1.get_name().clone(),
+            CurrentPlayer::Second => self.player2.get_name().clone(),
+        });
+        GameResult {
+            winner,
+            winner_name,
+            tampered,
+            forfeit: None,
+            record: self.record.clone(),
+            series: None,
+        }
+    }
+    fn save(&mut self) -> Option<Box<SavedGame>> {
+        let player1 = self.player1.save()?;
+        let player2 = self.player2.save()?;
+        Some(Box::new(
+            SavedGame::new(
+                self.config.clone(),
+                self.current_player,
+                self.record.clone(),
+                player1,
+                player2,
+            )
+            .with_arsenals(self.arsenals),
+        ))
+    }
+    fn play_turn(&mut self) -> Turn {
+        let action = self.choose_action();
+        self.carry_out(action)
+    }
+    // what the current player wants to do, nothing of it has happened yet
+    fn choose_action(&mut self) -> Action {
+        if self.config.rules.salvo {
+            let count = self.current().ships_afloat();
+            return self.players_mut().0.choose_salvo(count);
+        }
+        let arsenal = self.arsenals[self.current_player as usize - 1];
+        self.players_mut().0.choose_action(&arsenal)
+    }
+    fn carry_out(&mut self, action: Action) -> Turn {
+        if self.config.rules.salvo {
+            return self.play_salvo(action);
+        }
+        let point = match action {
+            Action::Shoot(point) => point,
+            Action::Strike(weapon, point) => return self.play_strike(weapon, point),
+            Action::Move(point, maneuver) => return self.play_move(Some((point, maneuver))),
+            Action::Moved => return self.play_move(None),
+            Action::Repair(point) => return self.play_repair(point),
+            Action::Salvo(points) => match points.first() {
+                Some(point) => *point,
+                None => return Turn::Retry,
+            },
+            Action::SaveAndQuit => return Turn::End(GameEnd::Quit(self.save())),
+            Action::Forfeit => return Turn::End(self.forfeit(self.current_player)),
+        };
+        let current = self.current_player;
+        let (curr, opp) = self.players_mut();
+        let Ok(shot) = opp.process_shot(point) else {
+            // write error and continue
+            return Turn::Retry;
+        };
+        let result = ShotOutcome::from(&shot);
+        curr.update_view_board(shot, point)
+            .expect("Out of bounds, unable to show this shot");
+        let again = self.config.rules.extra_turn.earned_by(&result);
+        let mine = result == ShotOutcome::Mine;
+        self.record.shot(current, point, result);
+        if mine {
+            self.set_off(&[point]);
+        }
+        if again { Turn::Again } else { Turn::Done }
+    }
+    // every mine the current player shot goes off in turn, each blast hits their own fleet and
+    // is recorded as a shot by the mine's owner
+    fn set_off(&mut self, mines: &[Point]) {
+        let current = self.current_player;
+        for &mine in mines {
+            let (curr, opp) = self.players_mut();
+            // once either fleet is gone the game is decided, the other mines are duds
+            if curr.is_game_over() || opp.is_game_over() {
+                break;
+            }
+            let Some((at, result)) = curr.take_blast(mine) else {
+                break;
+            };
+            opp.update_view_blast(at, &result)
+                .expect("Out of bounds, unable to show this blast");
+            self.record.shot(current.other(), at, result);
+        }
+    }
+    // the cells among the results that set off a mine
+    fn mines(results: &[(Point, ShotOutcome)]) -> Vec<Point> {
+        results
+            .iter()
+            .filter(|(_, result)| *result == ShotOutcome::Mine)
+            .map(|(p, _)| *p)
+            .collect()
+    }
+    // a special attack, the charge is only used up once the strike went off
+    fn play_strike(&mut self, weapon: Weapon, point: Point) -> Turn {
+        let current = self.current_player;
+        if self.arsenals[current as usize - 1].charges(weapon) == 0 {
+            return Turn::Retry;
+        }
+        let (curr, opp) = self.players_mut();
+        let Ok(strike) = opp.process_strike(weapon, point) else {
+            return Turn::Retry;
+        };
+        curr.update_view_strike(weapon, point, &strike)
+            .expect("Out of bounds, unable to show this strike");
+        self.arsenals[current as usize - 1].take(weapon);
+        let again = match strike {
+            Strike::Ping { count } => {
+                self.record.scan(current, point, count);
+                false
+            }
+            Strike::Shots { results } => {
+                let extra_turn = self.config.rules.extra_turn;
+                let again = results
+                    .iter()
+                    .any(|(_, result)| extra_turn.earned_by(result));
+                let mines = Self::mines(&results);
+                for (point, result) in results {
+                    self.record.shot(current, point, result);
+                }
+                self.set_off(&mines);
+                again
+            }
+        };
+        if again { Turn::Again } else { Turn::Done }
+    }
+    // a ship moves instead of the player firing, the opponent only learns that one did
+    fn play_move(&mut self, maneuver: Option<(Point, Maneuver)>) -> Turn {
+        if !self.config.rules.moving {
+            return Turn::Retry;
+        }
+        let current = self.current_player;
+        let (curr, opp) = self.players_mut();
+        let moved = match maneuver {
+            Some((point, maneuver)) => match curr.move_ship(point, maneuver) {
+                Ok(ship) => Some((ship, maneuver)),
+                Err(_) => return Turn::Retry,
+            },
+            // the ship moved on a board that lives elsewhere
+            None => None,
+        };
+        opp.update_view_move(moved);
+        self.record.moved(current, moved);
+        Turn::Done
+    }
+    // a hit is patched up instead of the player firing, the opponent sees where
+    fn play_repair(&mut self, point: Point) -> Turn {
+        if !self.config.rules.repair {
+            return Turn::Retry;
+        }
+        let current = self.current_player;
+        let (curr, opp) = self.players_mut();
+        if curr.repair_ship(point).is_err() {
+            return Turn::Retry;
+        }
+        opp.update_view_repair(point)
+            .expect("the repaired cell was a hit the opponent saw");
+        self.record.repaired(current, point);
+        Turn::Done
+    }
+    // one shot per ship still afloat, every target is picked before any of them is resolved
+    fn play_salvo(&mut self, action: Action) -> Turn {
+        let current = self.current_player;
+        let points = match action {
+            Action::Salvo(points) => points,
+            Action::Shoot(point) | Action::Strike(_, point) => vec![point],
+            // ships aren't moved or repaired in salvo games
+            Action::Move(..) | Action::Moved | Action::Repair(_) => return Turn::Retry,
+            Action::SaveAndQuit => return Turn::End(GameEnd::Quit(self.save())),
+            Action::Forfeit => return Turn::End(self.forfeit(current)),
+        };
+        let (curr, opp) = self.players_mut();
+        // shots at cells that were already hit are wasted, the turn still passes
+        let results: Vec<(Point, ShotOutcome)> = opp
+            .process_salvo(&points)
+            .into_iter()
+            .filter_map(|(p, res)| Some((p, res.ok()?)))
+            .collect();
+        curr.update_view_salvo(&results)
+            .expect("Out of bounds, unable to show this salvo");
+        // one good shot is enough for the whole salvo to go again
+        let extra_turn = self.config.rules.extra_turn;
+        let again = results
+            .iter()
+            .any(|(_, result)| extra_turn.earned_by(result));
+        let mines = Self::mines(&results);
+        for (point, result) in results {
+            self.record.shot(current, point, result);
+        }
+        self.set_off(&mines);
+        if again { Turn::Again } else { Turn::Done }
+    }
+    // both players pick what to do before any of it happens, handover is called before every
+    // pick, good shots don't earn extra turns
+    fn play_round(&mut self, mut handover: impl FnMut(&Self)) -> Turn {
+        let mut picks = Vec::new();
+        for player in [CurrentPlayer::First, CurrentPlayer::Second] {
+            self.current_player = player;
+            handover(self);
+            match self.choose_action() {
+                Action::SaveAndQuit => return Turn::End(GameEnd::Quit(self.save())),
+                Action::Forfeit => return Turn::End(self.forfeit(player)),
+                action => picks.push((player, action)),
+            }
+        }
+        // ships move and get patched up first, so the shots of both find the fleets the same
+        picks.sort_by_key(|(_, action)| {
+            !matches!(action, Action::Move(..) | Action::Moved | Action::Repair(_))
+        });
+        for (player, mut action) in picks {
+            self.current_player = player;
+            // only a pick that can't be carried out is picked again
+            loop {
+                match self.carry_out(action) {
+                    Turn::Retry => {
+                        if let Some(player) = self.left() {
+                            return Turn::End(self.forfeit(player));
+                        }
+                    }
+                    Turn::End(end) => return Turn::End(end),
+                    Turn::Done | Turn::Again => break,
+                }
+                handover(self);
+                action = self.choose_action();
+            }
+        }
+        Turn::Done
+    }
 }
 impl<T, U> GameMode for SinglePlayer<T, U>
 where
     T: GamePlayer,
     U: GamePlayer,
 {
-    fn run(mut self) -> GameResult {
+    fn play(&mut self) -> GameEnd {
         loop {
-            {
-                let (curr, opp) = self.players_mut();
-                let point = curr.choose_point();
-                let Ok(shot) = opp.process_shot(point) else {
-                    // write error and continue
-                    continue;
-                };
-                curr.update_view_board(shot, point)
-                    .expect("Out of bounds, unable to show this shot");
+            let turn = if self.config.rules.simultaneous {
+                self.play_round(|_| {})
+            } else {
+                self.play_turn()
+            };
+            // a player who left can't be waited for, whatever their turn did
+            if let Some(player) = self.left() {
+                return self.forfeit(player);
             }
+            let again = match turn {
+                Turn::Done => false,
+                Turn::Again => true,
+                Turn::Retry => continue,
+                Turn::End(end) => return end,
+            };
             if self.is_game_over() {
-                return GameResult {
-                    winner: self.current_player,
-                    winner_name: self.current().get_name().clone(),
-                };
+                return GameEnd::Over(Box::new(self.result()));
                 // TODO: write winner
             }
-            self.switch();
+            if !again {
+                self.switch();
+            }
         }
     }
 }
-impl<T, U> Setup<Vec<ShipBlueprint>> for SinglePlayer<T, U>
+impl<T, U> Setup<GameConfig> for SinglePlayer<T, U>
 where
     T: GamePlayer,
     U: GamePlayer,
 {
-    fn setup(&mut self, ships: Vec<ShipBlueprint>) {
+    fn setup(&mut self, config: GameConfig) {
+        let mut rng = rng::from_seed(config.seed);
+        self.config = config.clone();
+        self.arsenals = [config.rules.weapons; 2];
+        self.current_player = match config.rules.first_player {
+            FirstPlayer::First => CurrentPlayer::First,
+            FirstPlayer::Second => CurrentPlayer::Second,
+            FirstPlayer::Random if rng.random_bool(0.5) => CurrentPlayer::First,
+            FirstPlayer::Random => CurrentPlayer::Second,
+        };
+        self.player1.show_seed(config.seed);
+        self.player2.show_seed(config.seed);
+        // every player draws from its own stream so one's choices don't shift the other's
         // TODO: use lifetimes or this clone is okay?
-        self.player1.setup(ships.clone());
-        self.player2.setup(ships);
-        // let player = self.player();
+        self.player1.setup(config.clone().with_seed(rng.random()));
+        self.player2.setup(config.with_seed(rng.random()));
+        self.record = GameRecord::new(&self.config);
+        for (player, name, fleet) in [
+            (
+                CurrentPlayer::First,
+                self.player1.get_name(),
+                self.player1.fleet(),
+            ),
+            (
+                CurrentPlayer::Second,
+                self.player2.get_name(),
+                self.player2.fleet(),
+            ),
+        ] {
+            *self.record.player_mut(player) = RecordedPlayer {
+                name: name.clone(),
+                layout: fleet,
+            };
+        }
+        // both fleets are placed, remote players commit to theirs now
+        let (fleet1, fleet2) = (self.player1.fleet(), self.player2.fleet());
+        let (decoys1, decoys2) = (self.player1.decoys(), self.player2.decoys());
+        self.player1.commit(fleet2, decoys2);
+        self.player2.commit(fleet1, decoys1);
     }
 }
 
@@ -143,44 +576,258 @@ impl PlayerVsPlayerMode {
             term,
         }
     }
+    pub fn resume(
+        game: SinglePlayer<Player, Player>,
+        term: Rc<RefCell<ratatui::DefaultTerminal>>,
+    ) -> Self {
+        Self { game, term }
+    }
 }
 
-impl GameMode for PlayerVsPlayerMode {
-    fn run(mut self) -> GameResult {
+impl PlayerVsPlayerMode {
+    // the screen is hidden until the player about to pick is at the keyboard
+    fn handover(
+        term: &RefCell<ratatui::DefaultTerminal>,
+        mut lines: Vec<Line<'static>>,
+        game: &SinglePlayer<Player, Player>,
+    ) {
+        lines.push(Line::from(format!(
+            "Player {}: {}'s turn, press any key to continue.",
+            game.current_player as i32,
+            game.current().get_name(),
+        )));
+        let wait = WaitForKey::new(Text::from(lines));
+        sleep(Duration::from_secs(1));
+        let _ = term.borrow_mut().draw(|f| wait.render(f, f.area()));
+        wait.wait(None);
+    }
+    // every round both players pick in turn without seeing the other's pick
+    fn play_rounds(&mut self) -> GameEnd {
         loop {
+            let term = &self.term;
+            match self
+                .game
+                .play_round(|game| Self::handover(term, Vec::new(), game))
             {
-                let (curr, opp) = self.game.players_mut();
-                let point = curr.choose_point();
-                let Ok(shot) = opp.process_shot(point) else {
-                    // write error and continue
-                    continue;
-                };
-                curr.update_view_board(shot, point)
-                    .expect("Out of bounds, unable to show this shot");
+                Turn::End(end) => return end,
+                _ if self.game.is_game_over() => {
+                    return GameEnd::Over(Box::new(self.game.result()));
+                }
+                _ => {}
             }
+        }
+    }
+}
+
+impl GameMode for PlayerVsPlayerMode {
+    fn play(&mut self) -> GameEnd {
+        if self.game.config.rules.simultaneous {
+            return self.play_rounds();
+        }
+        // turns the current player earned with good shots, told to the next one at the handover
+        let mut extra = 0;
+        loop {
+            let again = match self.game.play_turn() {
+                Turn::Done => false,
+                Turn::Again => true,
+                Turn::Retry => continue,
+                Turn::End(end) => return end,
+            };
             if self.game.is_game_over() {
-                return GameResult {
-                    winner: self.game.current_player,
-                    winner_name: self.game.current().get_name().clone(),
-                };
+                return GameEnd::Over(Box::new(self.game.result()));
                 // TODO: write winner
             }
+            // the same player shoots again, there is nothing to hide
+            if again {
+                extra += 1;
+                continue;
+            }
             self.game.current().render();
+            let mut lines = Vec::new();
+            if extra > 0 {
+                lines.push(Line::from(format!(
+                    "Player {}: {} earned {extra} extra {} this turn.",
+                    self.game.current_player as i32,
+                    self.game.current().get_name(),
+                    if self.game.config.rules.salvo {
+                        "salvos"
+                    } else {
+                        "shots"
+                    },
+                )));
+                lines.push(Line::from(""));
+            }
+            extra = 0;
             self.game.switch();
             // TODO: Add a switch timer/click here
-            let wait = WaitForKey::new(Text::from(format!(
-                "Player {}: {}'s turn, press any key to continue.",
-                self.game.current_player as i32,
-                self.game.current().get_name(),
-            )));
-            sleep(Duration::from_secs(1));
-            self.term.borrow_mut().draw(|f| wait.render(f, f.area()));
-            wait.wait(None);
+            Self::handover(&self.term, lines, &self.game);
         }
     }
 }
-impl Setup<Vec<ShipBlueprint>> for PlayerVsPlayerMode {
-    fn setup(&mut self, ships: Vec<ShipBlueprint>) {
-        self.game.setup(ships);
+impl Setup<GameConfig> for PlayerVsPlayerMode {
+    fn setup(&mut self, config: GameConfig) {
+        self.game.setup(config);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        CurrentPlayer, GameEnd, GameMode, Setup, SinglePlayer,
+        config::{ExtraTurn, GameConfig},
+        player_board::ShotOutcome,
+        players::{Computer, Difficulty},
+    };
+
+    #[test]
+    fn salvo_fires_one_shot_per_ship_afloat() {
+        let mut config = GameConfig::default().with_seed(4);
+        config.rules.salvo = true;
+        let mut game = SinglePlayer::new(
+            Computer::new(Difficulty::Normal),
+            Computer::new(Difficulty::Hard),
+        );
+        game.setup(config.clone());
+        let GameEnd::Over(result) = game.run() else {
+            panic!("computers never quit");
+        };
+        let mut lost = [0, 0];
+        for turn in result.record.shots.chunk_by(|a, b| a.player == b.player) {
+            let shooter = turn[0].player;
+            let afloat = config.fleet.len() - lost[shooter as usize - 1];
+            assert_eq!(turn.len(), afloat);
+            let sunk = turn
+                .iter()
+                .filter(|shot| matches!(shot.result, ShotOutcome::Sunk { .. }))
+                .count();
+            lost[shooter.other() as usize - 1] += sunk;
+        }
+    }
+    #[test]
+    fn turn_passes_only_after_a_shot_without_an_extra_turn() {
+        for extra_turn in ExtraTurn::ALL {
+            let mut config = GameConfig::default().with_seed(9);
+            config.rules.extra_turn = extra_turn;
+            let mut game = SinglePlayer::new(
+                Computer::new(Difficulty::Normal),
+                Computer::new(Difficulty::Normal),
+            );
+            game.setup(config);
+            let GameEnd::Over(result) = game.run() else {
+                panic!("computers never quit");
+            };
+            let shots = &result.record.shots;
+            for pair in shots.windows(2) {
+                let again = extra_turn.earned_by(&pair[0].result);
+                assert_eq!(pair[0].player == pair[1].player, again);
+            }
+            assert_eq!(shots.last().map(|shot| shot.player), result.winner);
+        }
+    }
+    #[test]
+    fn simultaneous_rounds_fire_both_fleets_at_once() {
+        let mut config = GameConfig::default().with_seed(12);
+        config.rules.simultaneous = true;
+        config.rules.extra_turn = ExtraTurn::Hit;
+        let mut game = SinglePlayer::new(
+            Computer::new(Difficulty::Normal),
+            Computer::new(Difficulty::Hard),
+        );
+        game.setup(config);
+        let GameEnd::Over(result) = game.run() else {
+            panic!("computers never quit");
+        };
+        // every round is one shot by each, hits earn nothing extra
+        let shots = &result.record.shots;
+        assert!(shots.len().is_multiple_of(2));
+        for round in shots.chunks(2) {
+            assert_eq!(round[0].player, CurrentPlayer::First);
+            assert_eq!(round[1].player, CurrentPlayer::Second);
+        }
+        let winner = result.winner.expect("this seed doesn't end in a draw");
+        assert_eq!(result.record.winner, Some(winner));
+    }
+    #[test]
+    fn fleets_sunk_in_the_same_round_are_a_draw() {
+        // a single ship fills the whole board, every shot hits and both go down on the last one
+        let cells: Vec<String> = (0..16).map(|i| format!("[{}, {}]", i % 4, i / 4)).collect();
+        let toml = format!(
+            "[rules]\nwidth = 4\nheight = 4\nsimultaneous = true\n[[ships]]\nname = \"raft\"\nparts = [{}]\n",
+            cells.join(", ")
+        );
+        let config = GameConfig::from_toml(&toml).expect("should be valid");
+        let mut game = SinglePlayer::new(
+            Computer::new(Difficulty::Easy),
+            Computer::new(Difficulty::Easy),
+        );
+        game.setup(config);
+        let GameEnd::Over(result) = game.run() else {
+            panic!("computers never quit");
+        };
+        assert_eq!(result.winner, None);
+        assert_eq!(result.winner_name, None);
+        assert_eq!(result.record.winner, None);
+        assert_eq!(result.record.shots.len(), 32);
+    }
+    #[test]
+    fn armored_fleets_are_sunk_in_the_end() {
+        let mut config = GameConfig::default().with_seed(21);
+        for ship in &mut config.fleet {
+            *ship = ship.clone().with_hp(vec![2; ship.parts.len()]);
+        }
+        for difficulty in Difficulty::ALL {
+            let mut game = SinglePlayer::new(Computer::new(difficulty), Computer::new(difficulty));
+            game.setup(config.clone());
+            let GameEnd::Over(result) = game.run() else {
+                panic!("computers never quit");
+            };
+            let shots = &result.record.shots;
+            let damaged = shots
+                .iter()
+                .filter(|shot| shot.result == ShotOutcome::Damaged)
+                .count();
+            // every part of the loser's fleet took two hits
+            let cells: usize = config.fleet.iter().map(|ship| ship.parts.len()).sum();
+            let winner = shots
+                .iter()
+                .filter(|shot| Some(shot.player) == result.winner);
+            let winner_damaged = winner
+                .filter(|shot| shot.result == ShotOutcome::Damaged)
+                .count();
+            assert_eq!(winner_damaged, cells);
+            assert!(damaged >= cells);
+        }
+    }
+    #[test]
+    fn mines_blast_the_shooters_own_fleet() {
+        let config = GameConfig::from_toml(include_str!("../fleets/archipelago.toml"))
+            .expect("the bundled map is valid")
+            .with_seed(5);
+        let mut game = SinglePlayer::new(
+            Computer::new(Difficulty::Easy),
+            Computer::new(Difficulty::Normal),
+        );
+        game.setup(config.clone());
+        let GameEnd::Over(result) = game.run() else {
+            panic!("computers never quit");
+        };
+        let shots = &result.record.shots;
+        let mut mines = 0;
+        for (i, shot) in shots.iter().enumerate() {
+            assert!(!config.map.islands.contains(&shot.at));
+            if shot.result != ShotOutcome::Mine {
+                continue;
+            }
+            mines += 1;
+            assert!(config.map.mines.contains(&shot.at));
+            // the blast hits a ship of the player who shot, the turn passes as usual
+            let Some(blast) = shots.get(i + 1) else {
+                break;
+            };
+            assert_eq!(blast.player, shot.player.other());
+            let layout = result.record.player(shot.player).layout.as_ref();
+            assert!(layout.is_some_and(|ships| ships.iter().flatten().any(|p| *p == blast.at)));
+        }
+        assert!(mines > 0);
     }
 }
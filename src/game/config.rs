@@ -0,0 +1,646 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::player_board::ShotOutcome;
+use crate::game::player_board::{DEFAULT_HEIGHT, DEFAULT_WIDTH, Fit, PlayerBoard};
+use crate::game::point::Point;
+use crate::game::ship::ShipBlueprint;
+use crate::game::weapon::Arsenal;
+
+const DEFAULT_FLEET: &str = include_str!("../../fleets/default.toml");
+pub const MIN_BOARD_SIZE: usize = 4;
+pub const MAX_BOARD_SIZE: usize = 26;
+// ships are told apart by a u8 id on the board
+pub const MAX_SHIPS: usize = u8::MAX as usize + 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    Io(String),
+    Parse(String),
+    UnsupportedFormat(String),
+    NoShips,
+    EmptyShip(String),
+    ZeroCount(String),
+    DuplicatePart(String, Point),
+    DisconnectedShip(String),
+    InvalidBoardSize { width: usize, height: usize },
+    ShipTooLarge(String),
+    // the hit points don't list one number of at least 1 per part
+    InvalidHitPoints(String),
+    TooManyShips(usize),
+    // a cell of the map is off the board or listed twice
+    InvalidTerrain(Point),
+    FleetTooLarge { cells: usize, capacity: usize },
+    // there are enough cells, but no way was found to place every ship on them
+    FleetDoesNotFit,
+    // the search for a way to place the fleet gave up before finding one or ruling it out
+    FleetTooCrowded,
+    // there has to be at least one ship to sink
+    OnlyDecoys,
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "unable to read fleet file: {err}"),
+            Self::Parse(err) => write!(f, "malformed fleet file: {err}"),
+            Self::UnsupportedFormat(ext) => {
+                write!(
+                    f,
+                    "unsupported fleet file format '{ext}', use .toml or .json"
+                )
+            }
+            Self::NoShips => f.write_str("the fleet has no ships"),
+            Self::OnlyDecoys => f.write_str("every ship of the fleet is a decoy"),
+            Self::EmptyShip(name) => write!(f, "ship '{name}' has no parts"),
+            Self::ZeroCount(name) => write!(f, "ship '{name}' has a count of 0"),
+            Self::DuplicatePart(name, p) => {
+                write!(f, "ship '{name}' lists the part [{}, {}] twice", p.x, p.y)
+            }
+            Self::DisconnectedShip(name) => write!(f, "parts of ship '{name}' are not connected"),
+            Self::InvalidBoardSize { width, height } => write!(
+                f,
+                "a {width}x{height} board is not supported, both sides must be between {MIN_BOARD_SIZE} and {MAX_BOARD_SIZE}"
+            ),
+            Self::ShipTooLarge(name) => write!(f, "ship '{name}' does not fit on the board"),
+            Self::InvalidHitPoints(name) => write!(
+                f,
+                "ship '{name}' needs one hit point count of at least 1 for every part"
+            ),
+            Self::TooManyShips(count) => write!(
+                f,
+                "the fleet has {count} ships, at most {MAX_SHIPS} are supported"
+            ),
+            Self::InvalidTerrain(p) => write!(
+                f,
+                "the map cell [{}, {}] is off the board or listed twice",
+                p.x, p.y
+            ),
+            Self::FleetTooLarge { cells, capacity } => write!(
+                f,
+                "the fleet needs {cells} cells but the board only has {capacity}"
+            ),
+            Self::FleetDoesNotFit => f.write_str("the fleet can't be placed on the board"),
+            Self::FleetTooCrowded => f.write_str(
+                "no way to place the fleet on the board was found, it is too crowded to search through",
+            ),
+        }
+    }
+}
+impl Error for ConfigError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirstPlayer {
+    #[default]
+    First,
+    Second,
+    Random,
+}
+
+// When a shooter gets to fire again before the turn passes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtraTurn {
+    #[default]
+    Never,
+    // any hit, sinking a ship included
+    Hit,
+    // only sinking a ship
+    Sink,
+}
+impl ExtraTurn {
+    pub const ALL: [Self; 3] = [Self::Never, Self::Hit, Self::Sink];
+    // whether a shot with this result earns another one
+    pub fn earned_by(self, result: &ShotOutcome) -> bool {
+        match (self, result) {
+            (Self::Never, _) | (_, ShotOutcome::Miss | ShotOutcome::Mine) => false,
+            (Self::Hit, _) => true,
+            (Self::Sink, result) => matches!(result, ShotOutcome::Sunk { .. }),
+        }
+    }
+}
+impl Display for ExtraTurn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Never => "never",
+            Self::Hit => "hit",
+            Self::Sink => "sink",
+        })
+    }
+}
+impl FromStr for ExtraTurn {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|extra| extra.to_string() == s)
+            .ok_or_else(|| format!("unknown extra turn rule '{s}', use never, hit or sink"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub first_player: FirstPlayer,
+    pub width: usize,
+    pub height: usize,
+    // every turn fires one shot per ship the shooter still has afloat
+    pub salvo: bool,
+    // ships may not touch each other, not even at the corners
+    pub no_touch: bool,
+    // a good shot lets the shooter fire again, in salvo games the whole salvo goes again
+    pub extra_turn: ExtraTurn,
+    // special attack charges every player starts with, salvo turns can't use them
+    pub weapons: Arsenal,
+    // instead of firing a player may move an undamaged ship a cell or turn it, not in salvo turns
+    pub moving: bool,
+    // instead of firing a player may repair a destroyed part of a ship still afloat, once per part
+    pub repair: bool,
+    // both players pick their shots every round before any of them lands, no extra turns, and
+    // the game is a draw when both fleets go down in the same round
+    pub simultaneous: bool,
+}
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            first_player: FirstPlayer::default(),
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            salvo: false,
+            no_touch: false,
+            extra_turn: ExtraTurn::default(),
+            weapons: Arsenal::default(),
+            moving: false,
+            repair: false,
+            simultaneous: false,
+        }
+    }
+}
+
+// Terrain laid out before any ship is placed, both boards get the same.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Map {
+    // land, nothing is placed on or shot at it and both players see it from the start
+    pub islands: Vec<Point>,
+    // no ship is placed on a mine either, a shot at one is answered by a blast on the nearest
+    // ship part of the shooter's own fleet, it stays hidden from the shooter until then
+    pub mines: Vec<Point>,
+}
+impl Map {
+    // every cell no ship may be placed on
+    pub fn cells(&self) -> impl Iterator<Item = &Point> {
+        self.islands.iter().chain(&self.mines)
+    }
+}
+
+// the on-disk representation, validated into a GameConfig
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FleetFile {
+    #[serde(default)]
+    rules: Rules,
+    #[serde(default)]
+    map: Map,
+    ships: Vec<ShipEntry>,
+}
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShipEntry {
+    name: String,
+    parts: Vec<Point>,
+    // hits every part takes, in the order of the parts, one each when left out
+    #[serde(default)]
+    hp: Vec<u8>,
+    // shot at like any other ship, but it doesn't have to be sunk to win
+    #[serde(default)]
+    decoy: bool,
+    #[serde(default = "default_count")]
+    count: usize,
+}
+fn default_count() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub rules: Rules,
+    #[serde(default)]
+    pub map: Map,
+    pub fleet: Vec<ShipBlueprint>,
+    // not part of the fleet file, picked when the game starts
+    #[serde(skip)]
+    pub seed: u64,
+}
+impl GameConfig {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(format!("{}: {err}", path.display())))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            ext => Err(ConfigError::UnsupportedFormat(
+                ext.unwrap_or_default().to_string(),
+            )),
+        }
+    }
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let file: FleetFile =
+            toml::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        file.validate()
+    }
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let file: FleetFile =
+            serde_json::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))?;
+        file.validate()
+    }
+}
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_FLEET).expect("the bundled fleet is valid")
+    }
+}
+
+impl GameConfig {
+    // used to override the board size of a loaded fleet, e.g. from the command line
+    pub fn with_board_size(mut self, width: usize, height: usize) -> Result<Self, ConfigError> {
+        self.rules.width = width;
+        self.rules.height = height;
+        validate_fleet(&self.rules, &self.map, &self.fleet)?;
+        Ok(self)
+    }
+    // for configs that didn't come from a fleet file, like the one a network host sends
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.fleet.is_empty() {
+            return Err(ConfigError::NoShips);
+        }
+        for ship in &self.fleet {
+            let entry = ShipEntry {
+                name: ship.name.clone(),
+                parts: ship.parts.clone(),
+                hp: ship.hp.clone(),
+                decoy: ship.decoy,
+                count: 1,
+            };
+            entry.validate()?;
+        }
+        validate_fleet(&self.rules, &self.map, &self.fleet)
+    }
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl FleetFile {
+    fn validate(self) -> Result<GameConfig, ConfigError> {
+        if self.ships.is_empty() {
+            return Err(ConfigError::NoShips);
+        }
+        let mut fleet = Vec::new();
+        for entry in self.ships {
+            let parts = entry.validate()?;
+            for _ in 0..entry.count {
+                fleet.push(
+                    ShipBlueprint::new(parts.clone(), entry.name.clone())
+                        .with_hp(entry.hp.clone())
+                        .with_decoy(entry.decoy),
+                );
+            }
+        }
+        validate_fleet(&self.rules, &self.map, &fleet)?;
+        Ok(GameConfig {
+            rules: self.rules,
+            map: self.map,
+            fleet,
+            seed: 0,
+        })
+    }
+}
+
+impl ShipEntry {
+    // returns the parts shifted so that the ship touches both axes
+    fn validate(&self) -> Result<Vec<Point>, ConfigError> {
+        if self.parts.is_empty() {
+            return Err(ConfigError::EmptyShip(self.name.clone()));
+        }
+        if self.count == 0 {
+            return Err(ConfigError::ZeroCount(self.name.clone()));
+        }
+        let mut seen = HashSet::new();
+        for p in &self.parts {
+            if !seen.insert((p.x, p.y)) {
+                return Err(ConfigError::DuplicatePart(self.name.clone(), *p));
+            }
+        }
+        if !is_connected(&self.parts) {
+            return Err(ConfigError::DisconnectedShip(self.name.clone()));
+        }
+        if !self.hp.is_empty() && (self.hp.len() != self.parts.len() || self.hp.contains(&0)) {
+            return Err(ConfigError::InvalidHitPoints(self.name.clone()));
+        }
+
+        let min_x = self.parts.iter().map(|p| p.x).min().unwrap();
+        let min_y = self.parts.iter().map(|p| p.y).min().unwrap();
+        let parts: Vec<Point> = self
+            .parts
+            .iter()
+            .map(|p| Point::new(p.x - min_x, p.y - min_y))
+            .collect();
+        Ok(parts)
+    }
+}
+
+// checks the fleet against the board it is going to be played on
+fn validate_fleet(rules: &Rules, map: &Map, fleet: &[ShipBlueprint]) -> Result<(), ConfigError> {
+    let (board_width, board_height) = (rules.width, rules.height);
+    let size_range = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+    if !size_range.contains(&board_width) || !size_range.contains(&board_height) {
+        return Err(ConfigError::InvalidBoardSize {
+            width: board_width,
+            height: board_height,
+        });
+    }
+    for ship in fleet {
+        let width = ship.parts.iter().map(|p| p.x).max().unwrap_or(0) + 1;
+        let height = ship.parts.iter().map(|p| p.y).max().unwrap_or(0) + 1;
+        let fits = (width <= board_width && height <= board_height)
+            || (height <= board_width && width <= board_height);
+        if !fits {
+            return Err(ConfigError::ShipTooLarge(ship.name.clone()));
+        }
+    }
+    let mut terrain = HashSet::new();
+    for p in map.cells() {
+        if p.x >= board_width || p.y >= board_height || !terrain.insert(p) {
+            return Err(ConfigError::InvalidTerrain(*p));
+        }
+    }
+    if fleet.iter().all(|ship| ship.decoy) {
+        return Err(ConfigError::OnlyDecoys);
+    }
+    if fleet.len() > MAX_SHIPS {
+        return Err(ConfigError::TooManyShips(fleet.len()));
+    }
+    let (cells, capacity) = if rules.no_touch {
+        // every ship grown by a cell to the right and down still can't overlap another one,
+        // on a board that is a cell bigger in both directions
+        let grown = fleet.iter().map(|ship| {
+            let mut cells: Vec<Point> = ship
+                .parts
+                .iter()
+                .flat_map(|p| [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| (p.x + dx, p.y + dy)))
+                .map(Point::from)
+                .collect();
+            cells.sort_by_key(|p| (p.y, p.x));
+            cells.dedup();
+            cells.len()
+        });
+        (grown.sum(), (board_width + 1) * (board_height + 1))
+    } else {
+        let cells = fleet.iter().map(|ship| ship.parts.len()).sum();
+        (cells, board_width * board_height)
+    };
+    // the map takes its cells away from the fleet
+    let capacity = capacity.saturating_sub(terrain.len());
+    if cells > capacity {
+        return Err(ConfigError::FleetTooLarge { cells, capacity });
+    }
+    // the layouts of the computer fall back on the same search, so a fleet that passes always fits
+    let empty = PlayerBoard::new(board_width, board_height)
+        .with_no_touch(rules.no_touch)
+        .with_map(map);
+    match empty.fit_fleet(fleet) {
+        Fit::Placed(_) => Ok(()),
+        Fit::Impossible => Err(ConfigError::FleetDoesNotFit),
+        // not the fleet's fault as far as anyone knows, but nothing could be promised to place it
+        Fit::GaveUp => Err(ConfigError::FleetTooCrowded),
+    }
+}
+
+// flood fill from the first part, every part has to be reachable through edges
+fn is_connected(parts: &[Point]) -> bool {
+    let mut visited = vec![false; parts.len()];
+    let mut stack = vec![0];
+    visited[0] = true;
+    while let Some(i) = stack.pop() {
+        let current = parts[i];
+        for (j, other) in parts.iter().enumerate() {
+            if !visited[j] && current.x.abs_diff(other.x) + current.y.abs_diff(other.y) == 1 {
+                visited[j] = true;
+                stack.push(j);
+            }
+        }
+    }
+    visited.into_iter().all(|v| v)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        config::{ConfigError, ExtraTurn, FirstPlayer, GameConfig},
+        player_board::ShotOutcome,
+        point::Point,
+    };
+
+    #[test]
+    fn bundled_fleet_is_valid() {
+        let config = GameConfig::default();
+        assert_eq!(config.fleet.len(), 3);
+    }
+    #[test]
+    fn count_repeats_ship() {
+        let config = GameConfig::from_toml(
+            r#"
+            [rules]
+            first_player = "second"
+            [[ships]]
+            name = "cruiser"
+            parts = [[0, 0], [1, 0], [2, 0]]
+            count = 2
+            "#,
+        )
+        .expect("should be valid");
+        assert_eq!(config.rules.first_player, FirstPlayer::Second);
+        assert_eq!(config.fleet.len(), 2);
+        assert!(config.fleet.iter().all(|ship| ship.name == "cruiser"));
+    }
+    #[test]
+    fn parses_json() {
+        let config =
+            GameConfig::from_json(r#"{ "ships": [{ "name": "dot", "parts": [[0, 0]] }] }"#)
+                .expect("should be valid");
+        assert_eq!(config.rules.first_player, FirstPlayer::First);
+        assert_eq!(config.fleet[0].parts, vec![Point::new(0, 0)]);
+    }
+    #[test]
+    fn parts_are_moved_to_origin() {
+        let config = GameConfig::from_toml("[[ships]]\nname = \"dot\"\nparts = [[3, 4], [3, 5]]\n")
+            .expect("should be valid");
+        assert_eq!(
+            config.fleet[0].parts,
+            vec![Point::new(0, 0), Point::new(0, 1)]
+        );
+    }
+    #[test]
+    fn malformed_file_is_parse_error() {
+        let res = GameConfig::from_toml("[[ships]]\nname = \"dot\"\nparts = [[0]]\n");
+        assert!(matches!(res, Err(ConfigError::Parse(_))));
+        let res = GameConfig::from_toml("[[ships]]\nname = \"dot\"\nparts = [[0, 0]]\nsize = 1\n");
+        assert!(matches!(res, Err(ConfigError::Parse(_))));
+    }
+    #[test]
+    fn rejects_invalid_ships() {
+        let err = |s: &str| GameConfig::from_toml(s).expect_err("should be err");
+        assert_eq!(err("ships = []"), ConfigError::NoShips);
+        assert_eq!(
+            err("[[ships]]\nname = \"a\"\nparts = []\n"),
+            ConfigError::EmptyShip(String::from("a"))
+        );
+        assert_eq!(
+            err("[[ships]]\nname = \"a\"\nparts = [[0, 0]]\ncount = 0\n"),
+            ConfigError::ZeroCount(String::from("a"))
+        );
+        assert_eq!(
+            err("[[ships]]\nname = \"a\"\nparts = [[0, 0], [0, 0]]\n"),
+            ConfigError::DuplicatePart(String::from("a"), Point::new(0, 0))
+        );
+        assert_eq!(
+            err("[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 1]]\n"),
+            ConfigError::DisconnectedShip(String::from("a"))
+        );
+        assert_eq!(
+            err(
+                "[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 0], [2, 0], [3, 0], [4, 0], [5, 0], [6, 0], [7, 0], [8, 0], [9, 0], [10, 0]]\n"
+            ),
+            ConfigError::ShipTooLarge(String::from("a"))
+        );
+    }
+    #[test]
+    fn board_size_is_configurable() {
+        let config = GameConfig::from_toml(
+            "[rules]\nwidth = 20\nheight = 8\n[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 0], [2, 0], [3, 0], [4, 0], [5, 0], [6, 0], [7, 0], [8, 0], [9, 0], [10, 0]]\n",
+        )
+        .expect("should be valid");
+        assert_eq!((config.rules.width, config.rules.height), (20, 8));
+        assert_eq!(
+            config.clone().with_board_size(10, 10).err(),
+            Some(ConfigError::ShipTooLarge(String::from("a")))
+        );
+        assert_eq!(
+            config.with_board_size(3, 30).err(),
+            Some(ConfigError::InvalidBoardSize {
+                width: 3,
+                height: 30
+            })
+        );
+    }
+    #[test]
+    fn rejects_fleet_larger_than_board() {
+        let res = GameConfig::from_toml(
+            "[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 0], [0, 1], [1, 1]]\ncount = 26\n",
+        );
+        assert_eq!(
+            res.err(),
+            Some(ConfigError::FleetTooLarge {
+                cells: 104,
+                capacity: 100
+            })
+        );
+    }
+    #[test]
+    fn no_touch_fleet_needs_room_around_ships() {
+        let toml = "[rules]\nwidth = 5\nheight = 5\n[[ships]]\nname = \"a\"\nparts = [[0, 0]]\ncount = 10\n";
+        assert!(GameConfig::from_toml(toml).is_ok());
+        let no_touch = toml.replace("height = 5\n", "height = 5\nno_touch = true\n");
+        assert_eq!(
+            GameConfig::from_toml(&no_touch).err(),
+            Some(ConfigError::FleetTooLarge {
+                cells: 40,
+                capacity: 36
+            })
+        );
+    }
+    #[test]
+    fn fleet_that_cannot_be_placed_is_rejected() {
+        // the islands cut the board into four corners of two by two cells
+        let toml = "[rules]\nwidth = 5\nheight = 5\n[map]\nislands = [[2, 0], [2, 1], [2, 3], [2, 4], [0, 2], [1, 2], [2, 2], [3, 2], [4, 2]]\n[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 0], [2, 0]]\n";
+        assert_eq!(
+            GameConfig::from_toml(toml).err(),
+            Some(ConfigError::FleetDoesNotFit)
+        );
+        assert!(GameConfig::from_toml(&toml.replace(", [2, 0]]", "]")).is_ok());
+    }
+    #[test]
+    fn extra_turn_rule_is_read_from_the_fleet_file() {
+        let config = GameConfig::from_toml(
+            "[rules]\nextra_turn = \"sink\"\n[[ships]]\nname = \"a\"\nparts = [[0, 0]]\n",
+        )
+        .expect("should be valid");
+        assert_eq!(config.rules.extra_turn, ExtraTurn::Sink);
+        let sunk = ShotOutcome::Sunk {
+            parts: vec![Point::new(0, 0)],
+        };
+        assert!(ExtraTurn::Sink.earned_by(&sunk));
+        assert!(!ExtraTurn::Sink.earned_by(&ShotOutcome::Hit));
+        assert!(ExtraTurn::Hit.earned_by(&ShotOutcome::Hit));
+        assert!(!ExtraTurn::Hit.earned_by(&ShotOutcome::Miss));
+        assert!(!ExtraTurn::Never.earned_by(&sunk));
+        assert_eq!("hit".parse::<ExtraTurn>(), Ok(ExtraTurn::Hit));
+        assert!("always".parse::<ExtraTurn>().is_err());
+    }
+    #[test]
+    fn ships_can_have_armored_parts() {
+        let config = GameConfig::from_toml(
+            "[[ships]]\nname = \"battleship\"\nparts = [[0, 0], [1, 0], [2, 0]]\nhp = [1, 2, 1]\n",
+        )
+        .expect("should be valid");
+        assert_eq!(config.fleet[0].hp(), vec![1, 2, 1]);
+        assert!(config.validate().is_ok());
+        for hp in ["[1, 2]", "[1, 0, 1]"] {
+            let res = GameConfig::from_toml(&format!(
+                "[[ships]]\nname = \"a\"\nparts = [[0, 0], [1, 0], [2, 0]]\nhp = {hp}\n"
+            ));
+            assert_eq!(
+                res.err(),
+                Some(ConfigError::InvalidHitPoints(String::from("a")))
+            );
+        }
+    }
+    #[test]
+    fn maps_are_read_from_the_fleet_file() {
+        let toml = "[map]\nislands = [[4, 4], [4, 5]]\nmines = [[7, 7]]\n[[ships]]\nname = \"a\"\nparts = [[0, 0]]\n";
+        let config = GameConfig::from_toml(toml).expect("should be valid");
+        assert_eq!(config.map.islands, vec![Point::new(4, 4), Point::new(4, 5)]);
+        assert_eq!(config.map.mines, vec![Point::new(7, 7)]);
+        assert!(!ExtraTurn::Hit.earned_by(&ShotOutcome::Mine));
+        assert_eq!(
+            GameConfig::from_toml(&toml.replace("[7, 7]", "[4, 5]")).err(),
+            Some(ConfigError::InvalidTerrain(Point::new(4, 5)))
+        );
+        assert_eq!(
+            config.with_board_size(6, 6).err(),
+            Some(ConfigError::InvalidTerrain(Point::new(7, 7)))
+        );
+        let archipelago = GameConfig::from_toml(include_str!("../../fleets/archipelago.toml"))
+            .expect("the bundled map is valid");
+        assert_eq!(archipelago.map.mines.len(), 4);
+    }
+    #[test]
+    fn ships_can_be_decoys() {
+        let config = GameConfig::from_toml(include_str!("../../fleets/bluff.toml"))
+            .expect("the bundled fleet is valid");
+        assert_eq!(config.fleet.iter().filter(|ship| ship.decoy).count(), 2);
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            GameConfig::from_toml("[[ships]]\nname = \"a\"\nparts = [[0, 0]]\ndecoy = true\n")
+                .err(),
+            Some(ConfigError::OnlyDecoys)
+        );
+    }
+}
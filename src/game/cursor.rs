@@ -14,7 +14,9 @@ pub enum CursorError {
 impl Error for CursorError {}
 impl std::fmt::Display for CursorError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        match self {
+            Self::OutOfBounds => f.write_str("cursor moved out of bounds"),
+        }
     }
 }
 impl Cursor {
@@ -0,0 +1,540 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crossterm::event::{Event, KeyCode};
+use rand::Rng;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout as Split, Rect};
+use ratatui::text::Text;
+
+use crate::game::{
+    Setup,
+    config::{FirstPlayer, GameConfig, Rules},
+    cursor::Cursor,
+    player_board::ShotOutcome,
+    player_board::{BoardError, PlayerBoard, ShotError, ViewBoard, board_view::BoardView},
+    players::{Difficulty, Layout, Strategy, place_fleet, target},
+    point::Point,
+    rng::{self, GameRng},
+    tile::Tile,
+    ui::WaitForKey,
+};
+
+pub const MIN_PLAYERS: usize = 3;
+pub const MAX_PLAYERS: usize = 6;
+
+// A game between three or more players on boards of their own. Every turn the shooter picks an
+// opponent still afloat and a cell of theirs, whoever loses their fleet drops out and the last one
+// afloat wins. Turns are single shots, salvos, weapons, moves and repairs are two player rules.
+// The record format and the game over screen are made for two players, so a free-for-all ends
+// on its standings and seed and isn't recorded.
+pub struct FreeForAll {
+    contenders: Vec<Contender>,
+    config: GameConfig,
+    current: usize,
+    shots: Vec<AimedShot>,
+    // the players whose fleet is gone, the first one out first
+    out: Vec<usize>,
+}
+
+// One player of a free-for-all, a person at the keyboard or the computer.
+pub struct Contender {
+    name: String,
+    board: PlayerBoard,
+    // what is known of every board, by player, the player's own is never looked at
+    views: Vec<ViewBoard>,
+    mind: Mind,
+}
+enum Mind {
+    Human {
+        terminal: Rc<RefCell<ratatui::DefaultTerminal>>,
+        cursor: Point,
+        // the opponent being aimed at
+        focus: usize,
+    },
+    Computer(Box<Machine>),
+}
+// What a computer contender thinks with, boxed as it is far larger than a person's cursor.
+struct Machine {
+    difficulty: Difficulty,
+    layout: Layout,
+    // every opponent's fleet is hunted on its own, by player
+    strategies: Vec<Box<dyn Strategy>>,
+    rng: GameRng,
+}
+
+// A shot of the game, a mine going off is a shot by the owner of the mine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AimedShot {
+    pub shooter: usize,
+    pub target: usize,
+    pub at: Point,
+    pub result: ShotOutcome,
+}
+
+pub struct FreeForAllResult {
+    pub seed: u64,
+    pub names: Vec<String>,
+    pub winner: usize,
+    // the players whose fleet is gone, the first one out first
+    pub out: Vec<usize>,
+    pub shots: Vec<AimedShot>,
+}
+impl FreeForAllResult {
+    // every player, from the winner to the first one out
+    pub fn standings(&self) -> Vec<usize> {
+        [self.winner]
+            .into_iter()
+            .chain(self.out.iter().rev().copied())
+            .collect()
+    }
+}
+
+enum Pick {
+    Shot(usize, Point),
+    Quit,
+}
+
+// the first rule that is on and can't be played in a free-for-all
+pub fn unsupported(rules: &Rules) -> Option<&'static str> {
+    [
+        (rules.salvo, "salvo"),
+        (!rules.weapons.is_empty(), "special weapons"),
+        (rules.moving, "moving ships"),
+        (rules.repair, "repairs"),
+        (rules.simultaneous, "simultaneous turns"),
+    ]
+    .into_iter()
+    .find_map(|(on, rule)| on.then_some(rule))
+}
+
+impl Contender {
+    pub fn human(terminal: Rc<RefCell<ratatui::DefaultTerminal>>, name: String) -> Self {
+        Self::new(
+            name,
+            Mind::Human {
+                terminal,
+                cursor: Point::new(0, 0),
+                focus: 0,
+            },
+        )
+    }
+    pub fn computer(difficulty: Difficulty, name: String) -> Self {
+        Self::new(
+            name,
+            Mind::Computer(Box::new(Machine {
+                difficulty,
+                layout: difficulty.layout(),
+                strategies: Vec::new(),
+                rng: rng::from_seed(0),
+            })),
+        )
+    }
+    // only computers lay out their fleet by themselves
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        if let Mind::Computer(machine) = &mut self.mind {
+            machine.layout = layout;
+        }
+        self
+    }
+    fn new(name: String, mind: Mind) -> Self {
+        Self {
+            name,
+            board: PlayerBoard::new(0, 0),
+            views: Vec::new(),
+            mind,
+        }
+    }
+    // config carries the contender's own seed, the game's is the one shown to a person
+    fn setup(&mut self, config: &GameConfig, players: usize, game_seed: u64) {
+        let (width, height) = (config.rules.width, config.rules.height);
+        let no_touch = config.rules.no_touch;
+        let empty = PlayerBoard::new(width, height)
+            .with_no_touch(no_touch)
+            .with_map(&config.map);
+        self.views = (0..players)
+            .map(|_| {
+                ViewBoard::new(width, height)
+                    .with_no_touch(no_touch)
+                    .with_map(&config.map)
+            })
+            .collect();
+        match &mut self.mind {
+            Mind::Human {
+                terminal,
+                cursor,
+                focus,
+            } => {
+                self.board = empty;
+                *cursor = Point::new(0, 0);
+                *focus = 0;
+                place_fleet(terminal, &mut self.board, &config.fleet, Some(game_seed));
+            }
+            Mind::Computer(machine) => {
+                let Machine {
+                    difficulty,
+                    layout,
+                    strategies,
+                    rng,
+                } = machine.as_mut();
+                *rng = rng::from_seed(config.seed);
+                *strategies = (0..players)
+                    .map(|_| {
+                        let mut strategy = difficulty.strategy();
+                        strategy.setup(config);
+                        strategy
+                    })
+                    .collect();
+                // every layout ends with the search the fleet was validated with
+                self.board = layout
+                    .place(&empty, &config.fleet, rng)
+                    .expect("validation makes sure the fleet fits");
+            }
+        }
+    }
+    // which of the opponents to fire at and where
+    fn pick(&mut self, me: usize, opponents: &[usize], names: &[String]) -> Pick {
+        match &mut self.mind {
+            Mind::Computer(machine) => {
+                let Machine {
+                    strategies, rng, ..
+                } = machine.as_mut();
+                // a fleet that was hit is finished off before a new one is looked for
+                let hit = |view: &ViewBoard| {
+                    let hit =
+                        |tile: &Tile| matches!(tile, Tile::Hit | Tile::Damaged | Tile::Repaired);
+                    view.get_grid().iter().flatten().any(hit)
+                };
+                let wounded: Vec<usize> = opponents
+                    .iter()
+                    .copied()
+                    .filter(|&i| hit(&self.views[i]))
+                    .collect();
+                let pool = if wounded.is_empty() {
+                    opponents
+                } else {
+                    &wounded
+                };
+                let opponent = pool[rng.random_range(0..pool.len())];
+                let at = target(strategies[opponent].as_mut(), &self.views[opponent], rng);
+                Pick::Shot(opponent, at)
+            }
+            Mind::Human {
+                terminal,
+                cursor,
+                focus,
+            } => loop {
+                if !opponents.contains(focus) {
+                    *focus = opponents[0];
+                }
+                let status = format!(
+                    "Firing at {}: tab for the next opponent, esc to quit",
+                    names[*focus]
+                );
+                let (width, height) = (self.board.width(), self.board.height());
+                let aim = Cursor::new(cursor.x, cursor.y, width, height);
+                let mut aimed =
+                    BoardView::new(self.views[*focus].get_grid(), Some(aim), &names[*focus]);
+                let screen = Boards {
+                    status: &status,
+                    me,
+                    board: &self.board,
+                    views: &self.views,
+                    names,
+                    opponents,
+                    focus: *focus,
+                    aimed: &aimed,
+                };
+                let _ = terminal.borrow_mut().draw(|f| screen.render(f, f.area()));
+                let Ok(Event::Key(e)) = crossterm::event::read() else {
+                    continue;
+                };
+                if e.code == KeyCode::Tab {
+                    let i = opponents.iter().position(|i| i == focus).unwrap_or(0);
+                    *focus = opponents[(i + 1) % opponents.len()];
+                    continue;
+                }
+                let res = aimed.handle_key(e);
+                *cursor = aimed.cursor_pos().unwrap_or(*cursor);
+                match res {
+                    Err(BoardError::Shot(ShotError::ShotExit)) => {
+                        if confirm_quit(terminal) {
+                            return Pick::Quit;
+                        }
+                    }
+                    Ok(Some(at)) => return Pick::Shot(*focus, at),
+                    _ => continue,
+                }
+            },
+        }
+    }
+    // what a shot at the opponent's board did, whoever fired it
+    fn learn(&mut self, opponent: usize, at: Point, result: &ShotOutcome) {
+        if let Mind::Computer(machine) = &mut self.mind {
+            machine.strategies[opponent].register_shot(&result.as_shot());
+        }
+        self.views[opponent]
+            .register_shot(result.as_shot(), at)
+            .expect("Out of bounds, unable to show this shot");
+    }
+}
+
+fn confirm_quit(terminal: &RefCell<ratatui::DefaultTerminal>) -> bool {
+    let wait = WaitForKey::new(Text::from("Quit the game? (y/n)"));
+    let _ = terminal.borrow_mut().draw(|f| wait.render(f, f.area()));
+    wait.wait(Some(vec!['y', 'n'])) == 'y'
+}
+
+// Every board the player knows of in rows as wide as the screen allows, the own one last, the
+// one being aimed at is drawn from aimed so it shows the cursor.
+struct Boards<'a> {
+    status: &'a str,
+    me: usize,
+    board: &'a PlayerBoard,
+    views: &'a [ViewBoard],
+    names: &'a [String],
+    opponents: &'a [usize],
+    focus: usize,
+    aimed: &'a BoardView<'a>,
+}
+impl Boards<'_> {
+    fn render(&self, f: &mut Frame, rect: Rect) {
+        let board_width = self.board.width() as u16 + 2; // Width of a board + borders
+        let board_height = self.board.height() as u16 + 2; // Height of a board + borders
+        let count = self.views.len();
+        let columns = ((rect.width + 2) / (board_width + 2)).max(1) as usize;
+        let columns = columns.min(count);
+        let rows = count.div_ceil(columns) as u16;
+        let total_width = columns as u16 * (board_width + 2) - 2;
+        let total_height = rows * (board_height + 1) + 1;
+        let chunks = Split::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(total_height),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+        let inner_chunks = Split::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(total_width),
+                Constraint::Min(0),
+            ])
+            .split(chunks[1]);
+        let area = inner_chunks[1];
+        f.render_widget(Text::from(self.status).centered(), chunks[1]);
+        let others = (0..count).filter(|i| *i != self.me);
+        for (n, i) in others.chain([self.me]).enumerate() {
+            let (row, column) = ((n / columns) as u16, (n % columns) as u16);
+            let cell = Rect::new(
+                area.x + column * (board_width + 2),
+                area.y + 1 + row * (board_height + 1),
+                board_width,
+                board_height,
+            )
+            .intersection(rect);
+            let name = &self.names[i];
+            if i == self.me {
+                BoardView::new(self.board.get_grid(), None, "Your ships").render(f, cell);
+            } else if i == self.focus {
+                self.aimed.render(f, cell);
+            } else if self.opponents.contains(&i) {
+                BoardView::new(self.views[i].get_grid(), None, name).render(f, cell);
+            } else {
+                let title = format!("{name} (out)");
+                BoardView::new(self.views[i].get_grid(), None, &title).render(f, cell);
+            }
+        }
+    }
+}
+
+impl FreeForAll {
+    pub fn new(contenders: Vec<Contender>) -> Self {
+        Self {
+            contenders,
+            config: GameConfig::default(),
+            current: 0,
+            shots: Vec::new(),
+            out: Vec::new(),
+        }
+    }
+    fn alive(&self) -> Vec<usize> {
+        (0..self.contenders.len())
+            .filter(|i| !self.out.contains(i))
+            .collect()
+    }
+    // the player whose fleet just went down drops out
+    fn drop_out(&mut self, player: usize) {
+        if self.contenders[player].board.is_game_over() && !self.out.contains(&player) {
+            self.out.push(player);
+        }
+    }
+    // plays until one fleet is left afloat, None when a player quit
+    pub fn play(&mut self) -> Option<FreeForAllResult> {
+        let names: Vec<String> = self.contenders.iter().map(|c| c.name.clone()).collect();
+        loop {
+            let alive = self.alive();
+            if let [winner] = alive[..] {
+                return Some(FreeForAllResult {
+                    seed: self.config.seed,
+                    names,
+                    winner,
+                    out: self.out.clone(),
+                    shots: self.shots.clone(),
+                });
+            }
+            let shooter = self.current;
+            let opponents: Vec<usize> = alive.into_iter().filter(|i| *i != shooter).collect();
+            let (opponent, at) = match self.contenders[shooter].pick(shooter, &opponents, &names) {
+                Pick::Shot(opponent, at) if opponents.contains(&opponent) => (opponent, at),
+                Pick::Shot(..) => continue,
+                Pick::Quit => return None,
+            };
+            let Some(again) = self.fire(shooter, opponent, at) else {
+                continue;
+            };
+            if !again || self.out.contains(&shooter) {
+                self.current = self.next_after(shooter);
+            }
+        }
+    }
+    // the next player still afloat in turn order
+    fn next_after(&self, player: usize) -> usize {
+        let count = self.contenders.len();
+        (1..count)
+            .map(|step| (player + step) % count)
+            .find(|i| !self.out.contains(i))
+            .unwrap_or(player)
+    }
+    // every shot is seen by everyone, so nobody fires at a cell someone else already shot
+    fn announce(&mut self, owner: usize, at: Point, result: &ShotOutcome) {
+        for (i, contender) in self.contenders.iter_mut().enumerate() {
+            if i != owner {
+                contender.learn(owner, at, result);
+            }
+        }
+    }
+    // the shot of shooter at the opponent's board, None when the cell can't be shot, otherwise
+    // whether the shooter goes again
+    fn fire(&mut self, shooter: usize, opponent: usize, at: Point) -> Option<bool> {
+        let shot = self.contenders[opponent].board.process_shot(at).ok()?;
+        let result = ShotOutcome::from(&shot);
+        self.announce(opponent, at, &result);
+        let again = self.config.rules.extra_turn.earned_by(&result);
+        self.shots.push(AimedShot {
+            shooter,
+            target: opponent,
+            at,
+            result: result.clone(),
+        });
+        self.drop_out(opponent);
+        // a mine hits the shooter's own fleet unless the game is decided already
+        if result == ShotOutcome::Mine
+            && self.alive().len() > 1
+            && let Some((hit, blast)) = self.contenders[shooter].board.process_blast(at)
+        {
+            self.announce(shooter, hit, &blast);
+            self.shots.push(AimedShot {
+                shooter: opponent,
+                target: shooter,
+                at: hit,
+                result: blast,
+            });
+            self.drop_out(shooter);
+        }
+        Some(again)
+    }
+}
+impl Setup<GameConfig> for FreeForAll {
+    fn setup(&mut self, config: GameConfig) {
+        let mut rng = rng::from_seed(config.seed);
+        let players = self.contenders.len();
+        self.current = match config.rules.first_player {
+            FirstPlayer::First => 0,
+            FirstPlayer::Second => 1,
+            FirstPlayer::Random => rng.random_range(0..players),
+        };
+        self.shots.clear();
+        self.out.clear();
+        // every player draws from its own stream so one's choices don't shift the others'
+        for contender in &mut self.contenders {
+            contender.setup(
+                &config.clone().with_seed(rng.random()),
+                players,
+                config.seed,
+            );
+        }
+        self.config = config;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        Setup,
+        config::{FirstPlayer, GameConfig},
+        free_for_all::{Contender, FreeForAll, FreeForAllResult, unsupported},
+        players::Difficulty,
+    };
+
+    fn play(players: usize, seed: u64) -> FreeForAllResult {
+        let contenders = (0..players)
+            .map(|i| Contender::computer(Difficulty::ALL[i % 4], format!("Clanker {}", i + 1)))
+            .collect();
+        let mut game = FreeForAll::new(contenders);
+        game.setup(GameConfig::default().with_seed(seed));
+        game.play().expect("computers don't quit")
+    }
+
+    #[test]
+    fn the_last_fleet_afloat_wins() {
+        for (players, seed) in [(3, 1), (4, 2), (6, 3)] {
+            let result = play(players, seed);
+            let mut standings = result.standings();
+            assert_eq!(standings[0], result.winner);
+            standings.sort();
+            assert_eq!(standings, (0..players).collect::<Vec<_>>());
+            assert!(!result.out.contains(&result.winner));
+        }
+    }
+
+    #[test]
+    fn shots_go_at_opponents_still_afloat() {
+        let result = play(5, 7);
+        for shot in &result.shots {
+            assert_ne!(shot.shooter, shot.target);
+        }
+        // nobody fires once they are out, nor is fired at
+        for player in &result.out {
+            let last = result
+                .shots
+                .iter()
+                .rposition(|shot| shot.target == *player)
+                .expect("a fleet only goes down to shots");
+            let later = &result.shots[last + 1..];
+            assert!(later.iter().all(|shot| shot.target != *player));
+            assert!(later.iter().all(|shot| shot.shooter != *player));
+        }
+    }
+
+    #[test]
+    fn the_same_seed_plays_the_same_game() {
+        let (first, second) = (play(4, 11), play(4, 11));
+        assert_eq!(first.seed, 11);
+        assert_eq!(first.shots, second.shots);
+        assert_eq!(first.standings(), second.standings());
+    }
+
+    #[test]
+    fn two_player_rules_are_not_played() {
+        let mut config = GameConfig::default();
+        config.rules.first_player = FirstPlayer::Random;
+        assert_eq!(unsupported(&config.rules), None);
+        config.rules.repair = true;
+        assert_eq!(unsupported(&config.rules), Some("repairs"));
+        config.rules.salvo = true;
+        assert_eq!(unsupported(&config.rules), Some("salvo"));
+    }
+}
@@ -0,0 +1,649 @@
+pub mod commitment;
+pub mod protocol;
+
+use std::error::Error;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::game::{
+    config::{ConfigError, FirstPlayer, GameConfig},
+    net::protocol::{Message, PROTOCOL_VERSION},
+    players::RemotePlayer,
+    rng,
+};
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(std::io::Error),
+    Disconnected,
+    // nothing came for READ_TIMEOUT
+    TimedOut,
+    // a line longer than MAX_LINE, it is never read to its end
+    TooLong,
+    Malformed(String),
+    Unexpected(String),
+    VersionMismatch { ours: u32, theirs: u32 },
+    Rejected(String),
+    InvalidConfig(ConfigError),
+    // a rule the protocol has no messages for
+    Unsupported(&'static str),
+}
+impl Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "network error: {err}"),
+            Self::Disconnected => f.write_str("the opponent disconnected"),
+            Self::TimedOut => f.write_str("the opponent stopped answering"),
+            Self::TooLong => f.write_str("the opponent sent a message that is far too long"),
+            Self::Malformed(line) => write!(f, "the opponent sent a malformed message: {line}"),
+            Self::Unexpected(what) => write!(f, "the opponent sent {what} out of turn"),
+            Self::VersionMismatch { ours, theirs } => write!(
+                f,
+                "the opponent speaks protocol version {theirs}, this game speaks {ours}"
+            ),
+            Self::Rejected(reason) => write!(f, "the host refused the game: {reason}"),
+            Self::InvalidConfig(err) => write!(f, "the host sent an invalid fleet: {err}"),
+            Self::Unsupported(rule) => write!(f, "{rule} can't be played over the network"),
+        }
+    }
+}
+impl Error for NetError {}
+impl From<std::io::Error> for NetError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+// how long the other player gets for anything, placing a fleet or thinking over a shot included
+const READ_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+// far more than any message takes, a whole fleet and its reveal included
+const MAX_LINE: usize = 1 << 20;
+
+// A line based connection to the other player's game.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Self, NetError> {
+        Self::with_timeout(stream, READ_TIMEOUT)
+    }
+    fn with_timeout(stream: TcpStream, timeout: Duration) -> Result<Self, NetError> {
+        // shots are tiny and somebody is waiting for every one of them
+        stream.set_nodelay(true)?;
+        // a peer that stalls is as gone as one that hung up, both ways
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        self.writer.write_all(message.encode().as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+    pub fn recv(&mut self) -> Result<Message, NetError> {
+        let mut line = String::new();
+        let read = (&mut self.reader)
+            .take(MAX_LINE as u64 + 1)
+            .read_line(&mut line)
+            .map_err(|err| match err.kind() {
+                ErrorKind::WouldBlock | ErrorKind::TimedOut => NetError::TimedOut,
+                _ => NetError::Io(err),
+            })?;
+        if read == 0 {
+            return Err(NetError::Disconnected);
+        }
+        if line.len() > MAX_LINE {
+            return Err(NetError::TooLong);
+        }
+        Message::decode(&line).map_err(|_| NetError::Malformed(line.trim_end().to_string()))
+    }
+}
+
+// Waits for one opponent on the listener. The host's config decides the game, the returned
+// config has the first player settled so both sides agree on it.
+pub fn host(
+    listener: &TcpListener,
+    name: &str,
+    mut config: GameConfig,
+) -> Result<(RemotePlayer, GameConfig), NetError> {
+    // each side answers shots as they come, neither could keep its own picks secret
+    if config.rules.simultaneous {
+        return Err(NetError::Unsupported("simultaneous turns"));
+    }
+    let (stream, _) = listener.accept()?;
+    let mut conn = Connection::new(stream)?;
+    let Message::Hello {
+        version,
+        name: remote_name,
+    } = conn.recv()?
+    else {
+        return Err(NetError::Unexpected(String::from("a message before hello")));
+    };
+    if version != PROTOCOL_VERSION {
+        let _ = conn.send(&Message::Reject {
+            reason: format!("protocol version {PROTOCOL_VERSION} is required"),
+        });
+        return Err(NetError::VersionMismatch {
+            ours: PROTOCOL_VERSION,
+            theirs: version,
+        });
+    }
+
+    let host_starts = match config.rules.first_player {
+        FirstPlayer::First => true,
+        FirstPlayer::Second => false,
+        FirstPlayer::Random => rng::from_seed(config.seed).random_bool(0.5),
+    };
+    let mut remote_config = config.clone();
+    (config.rules.first_player, remote_config.rules.first_player) = if host_starts {
+        (FirstPlayer::First, FirstPlayer::Second)
+    } else {
+        (FirstPlayer::Second, FirstPlayer::First)
+    };
+    conn.send(&Message::Welcome {
+        version: PROTOCOL_VERSION,
+        name: name.to_string(),
+        config: remote_config,
+        you_start: !host_starts,
+    })?;
+    Ok((RemotePlayer::new(conn, remote_name), config))
+}
+
+// Connects to a host and takes over its rules and fleet. The local seed is kept.
+pub fn join(
+    addr: impl ToSocketAddrs,
+    name: &str,
+    seed: u64,
+) -> Result<(RemotePlayer, GameConfig), NetError> {
+    let mut conn = Connection::new(TcpStream::connect(addr)?)?;
+    conn.send(&Message::Hello {
+        version: PROTOCOL_VERSION,
+        name: name.to_string(),
+    })?;
+    match conn.recv()? {
+        Message::Welcome {
+            version,
+            name: remote_name,
+            config,
+            you_start,
+        } => {
+            if version != PROTOCOL_VERSION {
+                return Err(NetError::VersionMismatch {
+                    ours: PROTOCOL_VERSION,
+                    theirs: version,
+                });
+            }
+            config.validate().map_err(NetError::InvalidConfig)?;
+            if config.rules.simultaneous {
+                return Err(NetError::Unsupported("simultaneous turns"));
+            }
+            let mut config = config.with_seed(seed);
+            config.rules.first_player = if you_start {
+                FirstPlayer::First
+            } else {
+                FirstPlayer::Second
+            };
+            Ok((RemotePlayer::new(conn, remote_name), config))
+        }
+        Message::Reject { reason } => Err(NetError::Rejected(reason)),
+        _ => Err(NetError::Unexpected(String::from(
+            "a message before welcome",
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::game::{
+        CurrentPlayer, GameEnd, GameMode, GameResult, Setup, SinglePlayer,
+        config::{FirstPlayer, GameConfig},
+        net::{
+            self, Connection, NetError,
+            commitment::TamperError,
+            protocol::{Message, PROTOCOL_VERSION},
+        },
+        player_board::ShotOutcome,
+        players::{Computer, Difficulty, GamePlayer},
+        point::Point,
+        weapon::Arsenal,
+    };
+
+    fn finish(end: GameEnd) -> GameResult {
+        let GameEnd::Over(result) = end else {
+            panic!("computers never quit");
+        };
+        *result
+    }
+    fn listener() -> TcpListener {
+        TcpListener::bind("127.0.0.1:0").expect("loopback should be available")
+    }
+    // a connection to whatever peer does, and the peer's end of it
+    fn connected(timeout: Duration) -> (Connection, TcpStream) {
+        let listener = listener();
+        let addr = listener.local_addr().expect("listener has an address");
+        let peer = TcpStream::connect(addr).expect("should connect");
+        let (stream, _) = listener.accept().expect("should accept");
+        let conn = Connection::with_timeout(stream, timeout).expect("should wrap");
+        (conn, peer)
+    }
+
+    #[test]
+    fn stalled_peer_times_out() {
+        let (mut conn, mut peer) = connected(Duration::from_millis(50));
+        // half a message and then nothing
+        peer.write_all(b"{\"type\":").expect("should send");
+        assert!(matches!(conn.recv(), Err(NetError::TimedOut)));
+    }
+    #[test]
+    fn endless_line_is_cut_off() {
+        let (mut conn, mut peer) = connected(Duration::from_secs(5));
+        let flood = thread::spawn(move || {
+            let chunk = vec![b'x'; 64 * 1024];
+            // the writes fail once the connection is dropped
+            while peer.write_all(&chunk).is_ok() {}
+        });
+        assert!(matches!(conn.recv(), Err(NetError::TooLong)));
+        drop(conn);
+        flood.join().unwrap();
+    }
+
+    #[test]
+    fn handshake_agrees_on_first_player() {
+        let listener = listener();
+        let addr = listener.local_addr().expect("listener has an address");
+        let joiner = thread::spawn(move || net::join(addr, "page", 1));
+        let config = GameConfig::default().with_seed(3);
+        let (remote, host_config) = net::host(&listener, "mistr", config).expect("should host");
+        let (host, join_config) = joiner.join().unwrap().expect("should join");
+        assert_eq!(remote.get_name(), "page");
+        assert_eq!(host.get_name(), "mistr");
+        assert_ne!(
+            host_config.rules.first_player,
+            join_config.rules.first_player
+        );
+        assert_ne!(host_config.rules.first_player, FirstPlayer::Random);
+        assert_eq!(host_config.fleet, join_config.fleet);
+        assert_eq!(join_config.seed, 1);
+    }
+    #[test]
+    fn simultaneous_turns_are_not_hosted() {
+        let mut config = GameConfig::default();
+        config.rules.simultaneous = true;
+        // nobody connects, the host gives up before waiting for anyone
+        assert!(matches!(
+            net::host(&listener(), "mistr", config),
+            Err(NetError::Unsupported(_))
+        ));
+    }
+    #[test]
+    fn version_mismatch_is_rejected() {
+        let listener = listener();
+        let addr = listener.local_addr().expect("listener has an address");
+        let joiner = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).expect("should connect");
+            stream
+                .write_all(b"{\"type\":\"hello\",\"version\":999,\"name\":\"future\"}\n")
+                .expect("should send");
+            let mut conn = net::Connection::new(stream).expect("should wrap");
+            conn.recv()
+        });
+        let res = net::host(&listener, "mistr", GameConfig::default());
+        assert!(matches!(
+            res,
+            Err(NetError::VersionMismatch { theirs: 999, .. })
+        ));
+        assert!(matches!(
+            joiner.join().unwrap(),
+            Ok(net::protocol::Message::Reject { .. })
+        ));
+    }
+    // a joiner that gets as far as the host's commitment and then does what it's told
+    fn half_joined(addr: std::net::SocketAddr) -> Connection {
+        let stream = TcpStream::connect(addr).expect("should connect");
+        let mut conn = Connection::new(stream).expect("should wrap");
+        conn.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            name: String::from("page"),
+        })
+        .expect("should send");
+        assert!(matches!(conn.recv(), Ok(Message::Welcome { .. })));
+        assert!(matches!(conn.recv(), Ok(Message::Commit { .. })));
+        conn.send(&Message::Commit {
+            hash: String::from("never revealed"),
+        })
+        .expect("should send");
+        conn
+    }
+    // the host shoots first at whatever the joiner does, the host's result
+    fn host_against(
+        mut config: GameConfig,
+        joiner: impl FnOnce(Connection) + Send + 'static,
+    ) -> GameResult {
+        let listener = listener();
+        let addr = listener.local_addr().expect("listener has an address");
+        let joiner = thread::spawn(move || joiner(half_joined(addr)));
+        config.rules.first_player = FirstPlayer::First;
+        let (remote, config) = net::host(&listener, "mistr", config).expect("should host");
+        let mut game = SinglePlayer::new(Computer::new(Difficulty::Easy), remote);
+        game.setup(config);
+        let result = finish(game.run());
+        joiner.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn opponent_hanging_up_mid_game_forfeits() {
+        let result = host_against(GameConfig::default().with_seed(18), |mut conn| {
+            let Ok(Message::Shot { at }) = conn.recv() else {
+                panic!("the host shoots first");
+            };
+            conn.send(&Message::Result {
+                at,
+                result: ShotOutcome::Miss,
+            })
+            .expect("should send");
+            // the connection drops when it goes out of scope, on the joiner's turn
+        });
+        assert_eq!(result.winner, Some(CurrentPlayer::First));
+        // leaving isn't cheating, there just is no fleet to check
+        assert!(result.tampered.is_none());
+        let (name, why) = result.forfeit.expect("the joiner left");
+        assert_eq!(name, "page");
+        assert!(why.contains("disconnected"));
+        assert_eq!(result.record.shots.len(), 1);
+        assert!(result.record.player(CurrentPlayer::Second).layout.is_none());
+    }
+    #[test]
+    fn malformed_answer_forfeits() {
+        let result = host_against(GameConfig::default().with_seed(18), |mut conn| {
+            assert!(matches!(conn.recv(), Ok(Message::Shot { .. })));
+            conn.writer.write_all(b"not json\n").expect("should send");
+        });
+        assert_eq!(result.winner, Some(CurrentPlayer::First));
+        let (_, why) = result.forfeit.expect("the joiner is given up on");
+        assert!(why.contains("malformed"));
+        // the shot was never answered
+        assert!(result.record.shots.is_empty());
+    }
+    // the joiner answers the host's first shot with whatever lie makes of it
+    fn lie_about_first_shot(lie: fn(Point) -> ShotOutcome) -> GameResult {
+        let result = host_against(GameConfig::default().with_seed(19), move |mut conn| {
+            let Ok(Message::Shot { at }) = conn.recv() else {
+                panic!("the host shoots first");
+            };
+            conn.send(&Message::Result {
+                at,
+                result: lie(at),
+            })
+            .expect("should send");
+        });
+        // the liar gives the game up on the spot, nothing of the lie made it to the board
+        assert_eq!(result.winner, Some(CurrentPlayer::First));
+        assert!(result.forfeit.is_none());
+        assert!(result.record.shots.is_empty());
+        result
+    }
+    fn caught_lying(result: &GameResult) -> bool {
+        matches!(
+            &result.tampered,
+            Some((name, TamperError::ImpossibleResult { .. })) if name == "page"
+        )
+    }
+
+    #[test]
+    fn sinking_a_ship_off_the_board_is_caught() {
+        let result = lie_about_first_shot(|at| ShotOutcome::Sunk {
+            parts: vec![at, Point::new(99, 99)],
+        });
+        assert!(caught_lying(&result));
+    }
+    #[test]
+    fn sinking_a_ship_somewhere_else_is_caught() {
+        let result = lie_about_first_shot(|at| ShotOutcome::Decoy {
+            parts: vec![Point::new(at.x ^ 1, at.y)],
+        });
+        assert!(caught_lying(&result));
+    }
+    #[test]
+    fn sinking_parts_that_were_never_hit_is_caught() {
+        let result = lie_about_first_shot(|at| ShotOutcome::Sunk {
+            parts: vec![at, Point::new(at.x ^ 1, at.y)],
+        });
+        assert!(caught_lying(&result));
+    }
+    #[test]
+    fn sinking_more_ships_than_the_fleet_has_is_caught() {
+        let mut config = GameConfig::default().with_seed(20);
+        config.rules.salvo = true;
+        let ships = config.fleet.len();
+        let result = host_against(config, move |mut conn| {
+            let answer = |conn: &mut Connection, sunk: usize| {
+                let Ok(Message::Salvo { shots }) = conn.recv() else {
+                    panic!("the host fires a salvo");
+                };
+                let results = shots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &at)| match i < sunk {
+                        true => (at, ShotOutcome::Sunk { parts: vec![at] }),
+                        false => (at, ShotOutcome::Miss),
+                    })
+                    .collect();
+                conn.send(&Message::Results { results })
+                    .expect("should send");
+            };
+            // one ship goes down, then a whole salvo of them, one more than are left
+            answer(&mut conn, 1);
+            conn.send(&Message::Salvo {
+                shots: vec![Point::new(0, 0)],
+            })
+            .expect("should send");
+            assert!(matches!(conn.recv(), Ok(Message::Results { .. })));
+            answer(&mut conn, ships);
+        });
+        assert!(caught_lying(&result));
+        assert_eq!(result.winner, Some(CurrentPlayer::First));
+        // only the first salvo was believed
+        let sunk = result
+            .record
+            .shots
+            .iter()
+            .filter(|shot| matches!(shot.result, ShotOutcome::Sunk { .. }))
+            .count();
+        assert_eq!(sunk, 1);
+    }
+    // plays two computers against each other over the loopback, host and joiner results
+    fn loopback_game(config: GameConfig) -> (GameResult, GameResult) {
+        let listener = listener();
+        let addr = listener.local_addr().expect("listener has an address");
+        let joiner = thread::spawn(move || {
+            let (remote, config) = net::join(addr, "page", 5).expect("should join");
+            let mut game = SinglePlayer::new(Computer::new(Difficulty::Hard), remote);
+            game.setup(config);
+            finish(game.run())
+        });
+        let (remote, config) = net::host(&listener, "mistr", config).expect("should host");
+        let mut game = SinglePlayer::new(Computer::new(Difficulty::Normal), remote);
+        game.setup(config);
+        let host_result = finish(game.run());
+        (host_result, joiner.join().unwrap())
+    }
+
+    #[test]
+    fn full_game_over_loopback() {
+        let (host_result, join_result) = loopback_game(GameConfig::default().with_seed(11));
+        // each side sees the other from the opposite seat
+        assert_ne!(host_result.winner, join_result.winner);
+        // both computers answer truthfully
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        // both records know every fleet once they were revealed
+        for result in [&host_result, &join_result] {
+            assert!(result.record.players.iter().all(|p| p.layout.is_some()));
+        }
+        assert_eq!(
+            host_result.record.shots.len(),
+            join_result.record.shots.len()
+        );
+        if host_result.winner_name.as_deref() == Some("page") {
+            assert!(
+                join_result
+                    .winner_name
+                    .is_some_and(|name| name.starts_with("Clanker"))
+            );
+        } else {
+            assert!(
+                host_result
+                    .winner_name
+                    .is_some_and(|name| name.starts_with("Clanker"))
+            );
+            assert_eq!(join_result.winner_name.as_deref(), Some("mistr"));
+        }
+    }
+    #[test]
+    fn salvo_game_over_loopback() {
+        let mut config = GameConfig::default().with_seed(12);
+        config.rules.salvo = true;
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        let cells = |result: &GameResult| -> Vec<_> {
+            result.record.shots.iter().map(|shot| shot.at).collect()
+        };
+        assert_eq!(cells(&host_result), cells(&join_result));
+    }
+    #[test]
+    fn weapons_game_over_loopback() {
+        let mut config = GameConfig::default().with_seed(13);
+        config.rules.weapons = Arsenal::each(2);
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // radar counts are checked against the revealed fleets as well
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        assert!(!host_result.record.scans.is_empty());
+        let pings = |result: &GameResult| -> Vec<_> {
+            result
+                .record
+                .scans
+                .iter()
+                .map(|scan| (scan.at, scan.count))
+                .collect()
+        };
+        assert_eq!(pings(&host_result), pings(&join_result));
+        assert_eq!(
+            host_result.record.shots.len(),
+            join_result.record.shots.len()
+        );
+    }
+    #[test]
+    fn armored_game_over_loopback() {
+        let mut config = GameConfig::default().with_seed(14);
+        config.fleet[1] = config.fleet[1].clone().with_hp(vec![1, 2, 2, 1]);
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // the armor is read off the revealed fleets when the answers are checked
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        assert_eq!(
+            host_result.record.shots.len(),
+            join_result.record.shots.len()
+        );
+    }
+    #[test]
+    fn mined_game_over_loopback() {
+        let config = GameConfig::from_toml(include_str!("../../fleets/archipelago.toml"))
+            .expect("the bundled map is valid")
+            .with_seed(8);
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // both sides agree on every shot and every blast, and nobody lied about them
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        for result in [&host_result, &join_result] {
+            assert!(
+                result
+                    .record
+                    .shots
+                    .iter()
+                    .any(|shot| shot.result == ShotOutcome::Mine)
+            );
+        }
+        assert_eq!(
+            host_result.record.shots.len(),
+            join_result.record.shots.len()
+        );
+    }
+    #[test]
+    fn moving_game_over_loopback() {
+        let mut config = GameConfig::default().with_seed(15);
+        config.rules.moving = true;
+        config.rules.weapons = Arsenal::each(1);
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // every move is replayed against the fleets before the shots and pings are checked
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        for result in [&host_result, &join_result] {
+            assert!(!result.record.moves.is_empty());
+            assert!(result.record.moves.iter().all(|m| m.moved.is_some()));
+        }
+        assert_eq!(
+            host_result.record.moves.len(),
+            join_result.record.moves.len()
+        );
+    }
+    #[test]
+    fn repair_game_over_loopback() {
+        let mut config = GameConfig::default().with_seed(16);
+        config.rules.repair = true;
+        config.rules.moving = true;
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // the repairs are replayed along with the shots when the answers are checked
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        assert!(!host_result.record.repairs.is_empty());
+        let repaired = |result: &GameResult| -> Vec<_> {
+            result
+                .record
+                .repairs
+                .iter()
+                .map(|r| (r.after, r.at))
+                .collect()
+        };
+        assert_eq!(repaired(&host_result), repaired(&join_result));
+    }
+    #[test]
+    fn decoy_game_over_loopback() {
+        let config = GameConfig::from_toml(include_str!("../../fleets/bluff.toml"))
+            .expect("the bundled fleet is valid")
+            .with_seed(17);
+        let (host_result, join_result) = loopback_game(config);
+        assert_ne!(host_result.winner, join_result.winner);
+        // the decoys are told apart when the fleets are revealed and every answer still checks out
+        assert!(host_result.tampered.is_none());
+        assert!(join_result.tampered.is_none());
+        assert!(
+            host_result
+                .record
+                .shots
+                .iter()
+                .any(|shot| matches!(shot.result, ShotOutcome::Decoy { .. }))
+        );
+    }
+}
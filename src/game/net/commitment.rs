@@ -0,0 +1,806 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::game::{
+    config::Map,
+    net::NetError,
+    player_board::ShotOutcome,
+    point::Point,
+    rotation::Rotation,
+    ship::{self, Maneuver, ShipBlueprint},
+    weapon::Weapon,
+};
+
+const SALT_LEN: usize = 32;
+
+// Something the opponent claimed during the game that their revealed fleet contradicts, or that
+// no fleet could back up.
+#[derive(Debug)]
+pub enum TamperError {
+    // the opponent never revealed their fleet
+    NoReveal(NetError),
+    // the revealed fleet is not the one committed to at the start
+    Commitment,
+    // the revealed fleet could not have been placed under the rules of the game
+    InvalidFleet(String),
+    // a shot was answered with something else than what it really did
+    WrongResult {
+        at: Point,
+        reported: ShotOutcome,
+        actual: ShotOutcome,
+    },
+    // a radar ping counted a different number of ship cells than there were
+    WrongCount {
+        at: Point,
+        reported: usize,
+        actual: usize,
+    },
+    // a move that doesn't match what was sent during the game, or that the rules don't allow
+    IllegalMove(String),
+    // a repair of a cell that held no destroyed part of a ship afloat, or was repaired before
+    IllegalRepair(Point),
+    // an answer that can't be true whatever the fleet, caught as soon as it came in
+    ImpossibleResult {
+        at: Point,
+        reported: ShotOutcome,
+    },
+}
+impl Display for TamperError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoReveal(err) => write!(f, "the fleet was never revealed ({err})"),
+            Self::Commitment => f.write_str("the revealed fleet does not match the commitment"),
+            Self::InvalidFleet(why) => write!(f, "the revealed fleet is not legal: {why}"),
+            Self::WrongResult {
+                at,
+                reported,
+                actual,
+            } => write!(
+                f,
+                "the shot at ({}, {}) was reported as {reported:?} but was {actual:?}",
+                at.x, at.y
+            ),
+            Self::WrongCount {
+                at,
+                reported,
+                actual,
+            } => write!(
+                f,
+                "the radar at ({}, {}) was told of {reported} ship cells but there were {actual}",
+                at.x, at.y
+            ),
+            Self::IllegalMove(why) => write!(f, "a ship was moved illegally: {why}"),
+            Self::IllegalRepair(at) => {
+                write!(f, "the repair at ({}, {}) was not allowed", at.x, at.y)
+            }
+            Self::ImpossibleResult { at, reported } => write!(
+                f,
+                "the shot at ({}, {}) was answered with {reported:?}, which can't be",
+                at.x, at.y
+            ),
+        }
+    }
+}
+impl Error for TamperError {}
+
+// A fleet hidden behind a salted hash. The hash goes to the opponent when the game starts, the
+// salt and the fleet when it ends, so they can check every answer they were given. Every move
+// of a ship is committed to the same way once it's made.
+pub struct Commitment {
+    salt: [u8; SALT_LEN],
+    fleet: Vec<Vec<Point>>,
+    // where the fleet is after the moves
+    current: Vec<Vec<Point>>,
+    moves: Vec<([u8; SALT_LEN], usize, Maneuver)>,
+}
+// A move as it is revealed at the end, with the salt of the hash sent when it was made.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevealedMove {
+    pub ship: usize,
+    pub maneuver: Maneuver,
+    pub salt: String,
+}
+impl Commitment {
+    pub fn new(fleet: Vec<Vec<Point>>) -> Self {
+        // the salt must not be guessable by the opponent, so it doesn't come from the game seed
+        Self {
+            salt: rand::rng().random(),
+            current: fleet.clone(),
+            fleet,
+            moves: Vec::new(),
+        }
+    }
+    pub fn hash(&self) -> String {
+        digest(&self.salt, &self.fleet)
+    }
+    pub fn salt(&self) -> String {
+        to_hex(&self.salt)
+    }
+    pub fn fleet(&self) -> &[Vec<Point>] {
+        &self.fleet
+    }
+    // commits to the fleet after a move the board already allowed, returns the new hash
+    pub fn moved(&mut self, ship: usize, maneuver: Maneuver) -> String {
+        let parts = maneuver
+            .apply(&self.current[ship])
+            .expect("the board only allows moves that stay on it");
+        self.current[ship] = parts;
+        let salt = rand::rng().random();
+        self.moves.push((salt, ship, maneuver));
+        digest(&salt, &self.current)
+    }
+    pub fn moves(&self) -> Vec<RevealedMove> {
+        self.moves
+            .iter()
+            .map(|(salt, ship, maneuver)| RevealedMove {
+                ship: *ship,
+                maneuver: *maneuver,
+                salt: to_hex(salt),
+            })
+            .collect()
+    }
+}
+
+fn digest(salt: &[u8], fleet: &[Vec<Point>]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(serde_json::to_vec(fleet).expect("points always serialize"));
+    to_hex(&hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// checks that the revealed salt and fleet hash to what was committed
+pub fn check_commitment(hash: &str, salt: &str, fleet: &[Vec<Point>]) -> Result<(), TamperError> {
+    match from_hex(salt) {
+        Some(salt) if salt.len() == SALT_LEN && digest(&salt, fleet) == hash => Ok(()),
+        _ => Err(TamperError::Commitment),
+    }
+}
+
+// checks that the fleet fits on the board without overlaps and is made of the agreed ships
+pub fn check_fleet(
+    fleet: &[Vec<Point>],
+    width: usize,
+    height: usize,
+    blueprints: &[ShipBlueprint],
+    no_touch: bool,
+    map: &Map,
+) -> Result<(), TamperError> {
+    let invalid = |why: &str| Err(TamperError::InvalidFleet(String::from(why)));
+    if fleet.len() != blueprints.len() {
+        return invalid("wrong number of ships");
+    }
+    let mut taken = Vec::new();
+    for ship in fleet {
+        if ship.iter().any(|p| p.x >= width || p.y >= height) {
+            return invalid("a ship is off the board");
+        }
+        if ship.iter().any(|p| taken.contains(p)) {
+            return invalid("ships overlap");
+        }
+        if ship.iter().any(|p| map.cells().any(|q| q == p)) {
+            return invalid("a ship is on an island or a mine");
+        }
+        if no_touch
+            && ship
+                .iter()
+                .flat_map(|p| p.neighbours())
+                .any(|p| taken.contains(&p))
+        {
+            return invalid("ships touch");
+        }
+        taken.extend(ship);
+    }
+    if !fleet
+        .iter()
+        .zip(blueprints)
+        .all(|(ship, bp)| shaped_like(ship, bp))
+    {
+        return invalid("a ship is not part of the fleet");
+    }
+    Ok(())
+}
+
+// checks that the ships said to be decoys are exactly the ones built from decoy blueprints
+pub fn check_decoys(
+    fleet: &[Vec<Point>],
+    decoys: &[usize],
+    blueprints: &[ShipBlueprint],
+) -> Result<(), TamperError> {
+    if decoys.iter().any(|i| *i >= fleet.len()) {
+        return Err(TamperError::InvalidFleet(String::from(
+            "a decoy is not part of the fleet",
+        )));
+    }
+    if (0..fleet.len()).any(|i| decoys.contains(&i) != blueprints[i].decoy) {
+        return Err(TamperError::InvalidFleet(String::from(
+            "the decoys are not the agreed ones",
+        )));
+    }
+    Ok(())
+}
+
+// ships are revealed in the order of the fleet, each one has the shape of its own blueprint
+fn shaped_like(ship: &[Point], blueprint: &ShipBlueprint) -> bool {
+    blueprint.rotations().contains(&ship::normalize(ship))
+}
+
+// replays the revealed moves from the fleet committed to at the start, every fleet on the way
+// has to hash to what was sent when the move was made and pass the legal check, returns the
+// fleet before the first move and after every move
+pub fn check_moves(
+    fleet: &[Vec<Point>],
+    moves: &[RevealedMove],
+    hashes: &[String],
+    legal: impl Fn(&[Vec<Point>]) -> Result<(), TamperError>,
+) -> Result<Vec<Vec<Vec<Point>>>, TamperError> {
+    let illegal = |why: &str| Err(TamperError::IllegalMove(String::from(why)));
+    if moves.len() != hashes.len() {
+        return illegal("not every move was revealed");
+    }
+    let mut fleets = vec![fleet.to_vec()];
+    for (m, hash) in moves.iter().zip(hashes) {
+        let mut moved = fleets[fleets.len() - 1].clone();
+        let Some(parts) = moved.get(m.ship).and_then(|ship| m.maneuver.apply(ship)) else {
+            return illegal("a ship left the board");
+        };
+        moved[m.ship] = parts;
+        legal(&moved)?;
+        match from_hex(&m.salt) {
+            Some(salt) if salt.len() == SALT_LEN && digest(&salt, &moved) == *hash => {}
+            _ => return illegal("the fleet does not match what was committed after the move"),
+        }
+        fleets.push(moved);
+    }
+    Ok(fleets)
+}
+
+// replays every shot against the fleet and compares the answers, blasts from mines included,
+// the fleet switches to the next of fleets once as many shots as in moved_after were answered,
+// a repair comes before any move made after as many shots, the ships at the positions in decoys
+// are revealed as decoys when they go down
+pub fn check_shots(
+    fleets: &[Vec<Vec<Point>>],
+    moved_after: &[usize],
+    repairs: &[(usize, Point)],
+    decoys: &[usize],
+    blueprints: &[ShipBlueprint],
+    mines: &[Point],
+    shots: &[(Point, ShotOutcome)],
+) -> Result<(), TamperError> {
+    let Some(fleet) = fleets.first() else {
+        return Ok(());
+    };
+    // the hit points stay with the parts when a ship moves
+    let full = armor(fleet, blueprints)?;
+    let mut hp = full.clone();
+    let mut destroyed = vec![0; fleet.len()];
+    let mut repaired: Vec<(usize, usize)> = Vec::new();
+    let mut current = 0;
+    for n in 0..=shots.len() {
+        for (_, at) in repairs.iter().filter(|(after, _)| *after == n) {
+            let owner = fleets[current].iter().enumerate().find_map(|(i, ship)| {
+                let j = ship.iter().position(|p| p == at)?;
+                Some((i, j))
+            });
+            match owner {
+                Some((i, j))
+                    if hp[i][j] == 0
+                        && destroyed[i] < fleet[i].len()
+                        && !repaired.contains(&(i, j)) =>
+                {
+                    hp[i][j] = 1;
+                    destroyed[i] -= 1;
+                    repaired.push((i, j));
+                }
+                _ => return Err(TamperError::IllegalRepair(*at)),
+            }
+        }
+        while moved_after.get(current) == Some(&n) && current + 1 < fleets.len() {
+            let (before, after) = (&fleets[current], &fleets[current + 1]);
+            let battered = |i: usize| hp[i] != full[i] || repaired.iter().any(|(r, _)| *r == i);
+            if (0..fleet.len()).any(|i| before[i] != after[i] && battered(i)) {
+                return Err(TamperError::IllegalMove(String::from(
+                    "a damaged ship moved",
+                )));
+            }
+            current += 1;
+        }
+        let Some((at, reported)) = shots.get(n) else {
+            break;
+        };
+        let owner = fleets[current].iter().enumerate().find_map(|(i, ship)| {
+            let j = ship.iter().position(|p| p == at)?;
+            Some((i, j))
+        });
+        let actual = match owner {
+            None if mines.contains(at) => ShotOutcome::Mine,
+            None => ShotOutcome::Miss,
+            Some((i, j)) => {
+                let left = &mut hp[i][j];
+                *left = left.saturating_sub(1);
+                if *left > 0 {
+                    ShotOutcome::Damaged
+                } else {
+                    destroyed[i] += 1;
+                    if destroyed[i] == fleet[i].len() && decoys.contains(&i) {
+                        ShotOutcome::Decoy {
+                            parts: fleets[current][i].clone(),
+                        }
+                    } else if destroyed[i] == fleet[i].len() {
+                        ShotOutcome::Sunk {
+                            parts: fleets[current][i].clone(),
+                        }
+                    } else {
+                        ShotOutcome::Hit
+                    }
+                }
+            }
+        };
+        if !same_result(reported, &actual) {
+            return Err(TamperError::WrongResult {
+                at: *at,
+                reported: reported.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+// the hits every part of the revealed ships takes, from the blueprint at the ship's place in
+// the fleet
+fn armor(fleet: &[Vec<Point>], blueprints: &[ShipBlueprint]) -> Result<Vec<Vec<u8>>, TamperError> {
+    let invalid = || TamperError::InvalidFleet(String::from("a ship is not part of the fleet"));
+    if fleet.len() != blueprints.len() {
+        return Err(invalid());
+    }
+    fleet
+        .iter()
+        .zip(blueprints)
+        .map(|(ship, blueprint)| placed_hp(ship, blueprint).ok_or_else(invalid))
+        .collect()
+}
+
+// the blueprint's hit points when the ship was placed from it, the parts have to be listed in
+// the order of the blueprint unless they all take a single hit
+fn placed_hp(ship: &[Point], blueprint: &ShipBlueprint) -> Option<Vec<u8>> {
+    if ship.is_empty() || ship.len() != blueprint.parts.len() {
+        return None;
+    }
+    let hp = blueprint.hp();
+    let mut rotation = Rotation::None;
+    loop {
+        let rotated = blueprint.rotate(rotation);
+        let (first, origin) = (ship[0], rotated[0]);
+        // one offset moves every part of the blueprint onto the ship
+        let placed = first.x >= origin.x
+            && first.y >= origin.y
+            && ship.iter().zip(&rotated).all(|(p, part)| {
+                p.x + origin.x == part.x + first.x && p.y + origin.y == part.y + first.y
+            });
+        if placed {
+            return Some(hp);
+        }
+        rotation = rotation.next();
+        if rotation == Rotation::None {
+            break;
+        }
+    }
+    let unarmored = hp.iter().all(|hp| *hp == 1);
+    (unarmored && blueprint.rotations().contains(&ship::normalize(ship))).then_some(hp)
+}
+
+// counts the ship cells around every radar ping and compares the answers
+pub fn check_scans(
+    fleet: &[Vec<Point>],
+    width: usize,
+    height: usize,
+    scans: &[(Point, usize)],
+) -> Result<(), TamperError> {
+    for &(at, reported) in scans {
+        let actual = Weapon::Radar
+            .area(at, width, height)
+            .iter()
+            .filter(|p| fleet.iter().any(|ship| ship.contains(p)))
+            .count();
+        if reported != actual {
+            return Err(TamperError::WrongCount {
+                at,
+                reported,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+// the parts of a sunk ship may be listed in any order
+fn same_result(a: &ShotOutcome, b: &ShotOutcome) -> bool {
+    match (a, b) {
+        (ShotOutcome::Sunk { parts: a }, ShotOutcome::Sunk { parts: b })
+        | (ShotOutcome::Decoy { parts: a }, ShotOutcome::Decoy { parts: b }) => {
+            let (mut a, mut b) = (a.clone(), b.clone());
+            a.sort();
+            b.sort();
+            a == b
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        config::Map,
+        net::commitment::{self, Commitment, TamperError},
+        player_board::{PlayerBoard, ShotOutcome},
+        players::Layout,
+        point::Point,
+        rng,
+        ship::{Maneuver, ShipBlueprint},
+    };
+
+    fn fleet() -> Vec<Vec<Point>> {
+        vec![
+            vec![Point::new(0, 0), Point::new(1, 0)],
+            vec![Point::new(4, 2), Point::new(4, 3), Point::new(4, 4)],
+        ]
+    }
+    // in the order of fleet(), the way ships are revealed
+    fn blueprints() -> Vec<ShipBlueprint> {
+        vec![
+            ShipBlueprint::new(
+                vec![Point::new(0, 0), Point::new(1, 0)],
+                String::from("two"),
+            ),
+            ShipBlueprint::new(
+                vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+                String::from("three"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn commitment_only_opens_with_the_same_fleet() {
+        let commitment = Commitment::new(fleet());
+        let hash = commitment.hash();
+        assert!(commitment::check_commitment(&hash, &commitment.salt(), &fleet()).is_ok());
+
+        let mut moved = fleet();
+        moved[0] = vec![Point::new(0, 1), Point::new(1, 1)];
+        assert!(matches!(
+            commitment::check_commitment(&hash, &commitment.salt(), &moved),
+            Err(TamperError::Commitment)
+        ));
+        assert!(commitment::check_commitment(&hash, "00", &fleet()).is_err());
+        assert!(commitment::check_commitment(&hash, "not hex", &fleet()).is_err());
+    }
+    #[test]
+    fn salts_differ_between_games() {
+        assert_ne!(
+            Commitment::new(fleet()).hash(),
+            Commitment::new(fleet()).hash()
+        );
+    }
+    #[test]
+    fn fleet_must_follow_the_rules() {
+        assert!(
+            commitment::check_fleet(&fleet(), 10, 10, &blueprints(), false, &Map::default())
+                .is_ok()
+        );
+        assert!(
+            commitment::check_fleet(&fleet(), 4, 10, &blueprints(), false, &Map::default())
+                .is_err()
+        );
+        assert!(
+            commitment::check_fleet(&fleet()[..1], 10, 10, &blueprints(), false, &Map::default())
+                .is_err()
+        );
+
+        let mut overlapping = fleet();
+        overlapping[1] = vec![Point::new(1, 0), Point::new(1, 1), Point::new(1, 2)];
+        assert!(
+            commitment::check_fleet(&overlapping, 10, 10, &blueprints(), false, &Map::default())
+                .is_err()
+        );
+
+        // the ships are revealed in the order of the fleet
+        let swapped = vec![fleet()[1].clone(), fleet()[0].clone()];
+        assert!(
+            commitment::check_fleet(&swapped, 10, 10, &blueprints(), false, &Map::default())
+                .is_err()
+        );
+
+        let mut bent = fleet();
+        bent[1] = vec![Point::new(4, 2), Point::new(4, 3), Point::new(5, 3)];
+        assert!(
+            commitment::check_fleet(&bent, 10, 10, &blueprints(), false, &Map::default()).is_err()
+        );
+    }
+    #[test]
+    fn armored_parts_are_checked() {
+        let mut armored = blueprints();
+        armored[1] = armored[1].clone().with_hp(vec![1, 1, 3]);
+        // the last part of the three is the bottom one once it stands upright
+        let bottom = Point::new(4, 4);
+        let shots = [
+            (bottom, ShotOutcome::Damaged),
+            (bottom, ShotOutcome::Damaged),
+            (bottom, ShotOutcome::Hit),
+        ];
+        assert!(commitment::check_shots(&[fleet()], &[], &[], &[], &armored, &[], &shots).is_ok());
+        assert!(matches!(
+            commitment::check_shots(
+                &[fleet()],
+                &[],
+                &[],
+                &[],
+                &armored,
+                &[],
+                &[(bottom, ShotOutcome::Hit)]
+            ),
+            Err(TamperError::WrongResult {
+                actual: ShotOutcome::Damaged,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn armor_goes_by_the_place_in_the_fleet() {
+        // two ships of the same shape with different armor, and a bigger one placed before them
+        let blueprints = vec![
+            ShipBlueprint::new(vec![Point::new(0, 0), Point::new(1, 0)], String::from("a")),
+            ShipBlueprint::new(vec![Point::new(0, 0), Point::new(1, 0)], String::from("b"))
+                .with_hp(vec![2, 2]),
+            ShipBlueprint::new(
+                vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+                String::from("c"),
+            ),
+        ];
+        let board = Layout::Random
+            .place(
+                &PlayerBoard::new(10, 10),
+                &blueprints,
+                &mut rng::from_seed(5),
+            )
+            .expect("the fleet fits");
+        let fleet = board.fleet();
+        assert!(
+            commitment::check_fleet(&fleet, 10, 10, &blueprints, false, &Map::default()).is_ok()
+        );
+        // every part shot until each ship sank, answered by the board itself
+        let mut answering = board.clone();
+        let shots: Vec<(Point, ShotOutcome)> = fleet
+            .iter()
+            .flatten()
+            .flat_map(|&p| [p, p])
+            .filter_map(|p| {
+                let result = answering.process_shot(p).ok()?;
+                Some((p, ShotOutcome::from(&result)))
+            })
+            .collect();
+        assert!(shots.contains(&(fleet[1][0], ShotOutcome::Damaged)));
+        assert!(commitment::check_shots(&[fleet], &[], &[], &[], &blueprints, &[], &shots).is_ok());
+    }
+    #[test]
+    fn radar_counts_are_checked() {
+        let scans = [(Point::new(0, 1), 2), (Point::new(8, 8), 0)];
+        assert!(commitment::check_scans(&fleet(), 10, 10, &scans).is_ok());
+        assert!(matches!(
+            commitment::check_scans(&fleet(), 10, 10, &[(Point::new(4, 4), 1)]),
+            Err(TamperError::WrongCount {
+                reported: 1,
+                actual: 2,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn touching_ships_break_the_no_touch_rule() {
+        assert!(
+            commitment::check_fleet(&fleet(), 10, 10, &blueprints(), true, &Map::default()).is_ok()
+        );
+        let mut touching = fleet();
+        touching[1] = vec![Point::new(2, 1), Point::new(2, 2), Point::new(2, 3)];
+        assert!(
+            commitment::check_fleet(&touching, 10, 10, &blueprints(), false, &Map::default())
+                .is_ok()
+        );
+        assert!(
+            commitment::check_fleet(&touching, 10, 10, &blueprints(), true, &Map::default())
+                .is_err()
+        );
+    }
+    #[test]
+    fn honest_answers_pass() {
+        let shots = [
+            (Point::new(5, 5), ShotOutcome::Miss),
+            (Point::new(1, 0), ShotOutcome::Hit),
+            (
+                Point::new(0, 0),
+                ShotOutcome::Sunk {
+                    parts: vec![Point::new(1, 0), Point::new(0, 0)],
+                },
+            ),
+        ];
+        assert!(
+            commitment::check_shots(&[fleet()], &[], &[], &[], &blueprints(), &[], &shots).is_ok()
+        );
+    }
+    #[test]
+    fn lies_are_caught() {
+        let hidden_hit = [(Point::new(4, 3), ShotOutcome::Miss)];
+        assert!(matches!(
+            commitment::check_shots(&[fleet()], &[], &[], &[], &blueprints(), &[], &hidden_hit),
+            Err(TamperError::WrongResult {
+                actual: ShotOutcome::Hit,
+                ..
+            })
+        ));
+        let early_sink = [(
+            Point::new(0, 0),
+            ShotOutcome::Sunk {
+                parts: vec![Point::new(0, 0), Point::new(1, 0)],
+            },
+        )];
+        assert!(
+            commitment::check_shots(&[fleet()], &[], &[], &[], &blueprints(), &[], &early_sink)
+                .is_err()
+        );
+    }
+    #[test]
+    fn terrain_is_checked() {
+        let map = Map {
+            islands: vec![Point::new(4, 4)],
+            mines: vec![Point::new(7, 7)],
+        };
+        assert!(commitment::check_fleet(&fleet(), 10, 10, &blueprints(), false, &map).is_err());
+        let mut moved = fleet();
+        moved[1] = vec![Point::new(6, 2), Point::new(6, 3), Point::new(6, 4)];
+        assert!(commitment::check_fleet(&moved, 10, 10, &blueprints(), false, &map).is_ok());
+
+        let shots = [(Point::new(7, 7), ShotOutcome::Mine)];
+        assert!(
+            commitment::check_shots(
+                &[moved.clone()],
+                &[],
+                &[],
+                &[],
+                &blueprints(),
+                &map.mines,
+                &shots
+            )
+            .is_ok()
+        );
+        let hidden_mine = [(Point::new(7, 7), ShotOutcome::Miss)];
+        assert!(matches!(
+            commitment::check_shots(
+                &[moved.clone()],
+                &[],
+                &[],
+                &[],
+                &blueprints(),
+                &map.mines,
+                &hidden_mine
+            ),
+            Err(TamperError::WrongResult {
+                actual: ShotOutcome::Mine,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn moves_are_checked() {
+        let legal = |fleet: &[Vec<Point>]| {
+            commitment::check_fleet(fleet, 10, 10, &blueprints(), false, &Map::default())
+        };
+        let mut commitment = Commitment::new(fleet());
+        let hashes = [commitment.moved(1, Maneuver::Right)];
+        let moves = commitment.moves();
+        let fleets =
+            commitment::check_moves(&fleet(), &moves, &hashes, legal).expect("the move is honest");
+        assert_eq!(fleets[1][1][0], Point::new(5, 2));
+        assert!(matches!(
+            commitment::check_moves(&fleet(), &moves, &[commitment.hash()], legal),
+            Err(TamperError::IllegalMove(_))
+        ));
+        assert!(matches!(
+            commitment::check_moves(&fleet(), &[], &hashes, legal),
+            Err(TamperError::IllegalMove(_))
+        ));
+
+        // the same cell misses before the move and hits after it
+        let shots = [
+            (Point::new(5, 2), ShotOutcome::Miss),
+            (Point::new(5, 2), ShotOutcome::Hit),
+        ];
+        assert!(
+            commitment::check_shots(&fleets, &[1], &[], &[], &blueprints(), &[], &shots).is_ok()
+        );
+        assert!(
+            commitment::check_shots(&fleets, &[0], &[], &[], &blueprints(), &[], &shots).is_err()
+        );
+        // a ship that was hit may not move anymore
+        let damaged = [(Point::new(4, 2), ShotOutcome::Hit)];
+        assert!(matches!(
+            commitment::check_shots(&fleets, &[1], &[], &[], &blueprints(), &[], &damaged),
+            Err(TamperError::IllegalMove(_))
+        ));
+    }
+    #[test]
+    fn repairs_are_checked() {
+        let hit = (Point::new(4, 2), ShotOutcome::Hit);
+        // the repaired part has to be destroyed again before the ship sinks
+        let shots = [
+            hit.clone(),
+            (Point::new(4, 3), ShotOutcome::Hit),
+            (Point::new(4, 4), ShotOutcome::Hit),
+            hit.clone(),
+        ];
+        let repairs = [(1, Point::new(4, 2))];
+        assert!(
+            commitment::check_shots(&[fleet()], &[], &repairs, &[], &blueprints(), &[], &shots)
+                .is_err()
+        );
+        let mut sunk = shots.clone();
+        sunk[3].1 = ShotOutcome::Sunk {
+            parts: fleet()[1].clone(),
+        };
+        assert!(
+            commitment::check_shots(&[fleet()], &[], &repairs, &[], &blueprints(), &[], &sunk)
+                .is_ok()
+        );
+        // a cell is repaired once, and only after it was destroyed
+        let twice = [(1, Point::new(4, 2)), (1, Point::new(4, 2))];
+        let early = [(0, Point::new(4, 2))];
+        for repairs in [&twice[..], &early[..]] {
+            assert!(matches!(
+                commitment::check_shots(&[fleet()], &[], repairs, &[], &blueprints(), &[], &sunk),
+                Err(TamperError::IllegalRepair(_))
+            ));
+        }
+    }
+    #[test]
+    fn decoys_are_checked() {
+        let mut blueprints = blueprints();
+        blueprints[0] = blueprints[0].clone().with_decoy(true);
+        assert!(commitment::check_decoys(&fleet(), &[0], &blueprints).is_ok());
+        for decoys in [&[][..], &[1], &[0, 1], &[2]] {
+            assert!(matches!(
+                commitment::check_decoys(&fleet(), decoys, &blueprints),
+                Err(TamperError::InvalidFleet(_))
+            ));
+        }
+        let decoy = fleet()[0].clone();
+        let shots = [
+            (Point::new(0, 0), ShotOutcome::Hit),
+            (Point::new(1, 0), ShotOutcome::Decoy { parts: decoy }),
+        ];
+        assert!(
+            commitment::check_shots(&[fleet()], &[], &[], &[0], &blueprints, &[], &shots).is_ok()
+        );
+        // only the ships revealed as decoys may go down as one
+        assert!(matches!(
+            commitment::check_shots(&[fleet()], &[], &[], &[], &blueprints, &[], &shots),
+            Err(TamperError::WrongResult { .. })
+        ));
+    }
+}
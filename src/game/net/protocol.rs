@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    config::GameConfig,
+    net::commitment::RevealedMove,
+    player_board::ShotOutcome,
+    point::Point,
+    weapon::{Strike, Weapon},
+};
+
+// Bump this whenever a message changes shape or meaning, peers with different versions refuse
+// to play.
+pub const PROTOCOL_VERSION: u32 = 11;
+
+// Every message is one line of JSON tagged by "type", e.g. {"type":"shot","at":[3,4]}.
+//
+// A game goes like this:
+//   joiner -> host    hello     the joiner's protocol version and name
+//   host   -> joiner  welcome   the host's name, the rules and fleet, and who shoots first
+//                     (or reject with a reason, after which the host hangs up)
+//   both              commit    a salted hash of the local fleet, sent once it is placed, each
+//                               side waits for the other
+//   shooter           shot      the cell the current player fires at
+//   target            result    what that shot did, then the other side shoots
+//   shooter           salvo     instead of shot under the salvo rule, every target of the turn
+//   target            results   what each shot of the salvo did, in the same order
+//   shooter           strike    instead of shot, a special weapon and the cell it is aimed at
+//   target            struck    what the strike did, the radar count or every cell it shot
+//   shooter           blast     after a result that set off a mine, the part of the shooter's
+//                               own fleet the blast hit, one per mine in the order they went off
+//   shooter           moved     instead of shot under the moving ships rule, a salted hash of the
+//                               fleet after the move, which ship moved stays hidden
+//   shooter           repaired  instead of shot under the repair rule, the hit cell that was
+//                               repaired
+//   both              reveal    the salt and the fleet once the game is over, which of its ships
+//                               are decoys, then every move with the salt of its hash, so every
+//                               result can be checked against the commitments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    Hello {
+        version: u32,
+        name: String,
+    },
+    Welcome {
+        version: u32,
+        name: String,
+        config: GameConfig,
+        you_start: bool,
+    },
+    Reject {
+        reason: String,
+    },
+    Commit {
+        hash: String,
+    },
+    Shot {
+        at: Point,
+    },
+    Result {
+        at: Point,
+        result: ShotOutcome,
+    },
+    Salvo {
+        shots: Vec<Point>,
+    },
+    Results {
+        results: Vec<(Point, ShotOutcome)>,
+    },
+    Strike {
+        weapon: Weapon,
+        at: Point,
+    },
+    Struck {
+        at: Point,
+        result: Strike,
+    },
+    Blast {
+        at: Point,
+        result: ShotOutcome,
+    },
+    Moved {
+        hash: String,
+    },
+    Repaired {
+        at: Point,
+    },
+    Reveal {
+        salt: String,
+        fleet: Vec<Vec<Point>>,
+        #[serde(default)]
+        decoys: Vec<usize>,
+        #[serde(default)]
+        moves: Vec<RevealedMove>,
+    },
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("messages always serialize");
+        line.push('\n');
+        line
+    }
+    pub fn decode(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        config::GameConfig,
+        net::protocol::{Message, PROTOCOL_VERSION},
+        player_board::ShotOutcome,
+        point::Point,
+    };
+
+    #[test]
+    fn messages_are_single_lines() {
+        let messages = [
+            Message::Hello {
+                version: PROTOCOL_VERSION,
+                name: String::from("page"),
+            },
+            Message::Welcome {
+                version: PROTOCOL_VERSION,
+                name: String::from("mistr"),
+                config: GameConfig::default(),
+                you_start: true,
+            },
+            Message::Commit {
+                hash: String::from("c0ffee"),
+            },
+            Message::Shot {
+                at: Point::new(3, 4),
+            },
+            Message::Result {
+                at: Point::new(3, 4),
+                result: ShotOutcome::Sunk {
+                    parts: vec![Point::new(3, 4), Point::new(3, 5)],
+                },
+            },
+            Message::Reveal {
+                salt: String::from("5a17"),
+                fleet: vec![vec![Point::new(3, 4), Point::new(3, 5)]],
+                decoys: vec![0],
+                moves: Vec::new(),
+            },
+        ];
+        for message in messages {
+            let line = message.encode();
+            assert_eq!(line.matches('\n').count(), 1);
+            assert!(line.ends_with('\n'));
+            let decoded = Message::decode(&line).expect("should decode");
+            assert_eq!(decoded.encode(), line);
+        }
+    }
+    #[test]
+    fn shot_format_is_stable() {
+        let line = Message::Shot {
+            at: Point::new(3, 4),
+        }
+        .encode();
+        assert_eq!(line, "{\"type\":\"shot\",\"at\":[3,4]}\n");
+    }
+    #[test]
+    fn garbage_does_not_decode() {
+        assert!(Message::decode("{\"type\":\"launch_nukes\"}").is_err());
+        assert!(Message::decode("hello").is_err());
+    }
+}
@@ -4,15 +4,23 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Display;
 
+use serde::{Deserialize, Serialize};
+
+use crate::game::config::Map;
 use crate::game::point::Point;
 use crate::game::rotation::Rotation;
-use crate::game::ship::{Ship, ShipBlueprint};
+use crate::game::ship::{Damage, Maneuver, Ship, ShipBlueprint};
 use crate::game::tile::Tile;
+use crate::game::weapon::{Strike, Weapon};
 #[derive(Debug, PartialEq, Eq)]
 pub enum BoardError {
     ShipPlacementError(PlacementError),
+    ShipMoveError(MoveError),
+    ShipRepairError(RepairError),
     ShotRegisterError,
     Shot(ShotError),
+    // the board lives with a player who left the game
+    Gone,
 }
 #[derive(Debug, PartialEq, Eq)]
 pub enum ShotError {
@@ -20,12 +28,36 @@ pub enum ShotError {
     HiddenDoesntExistOnPlayerBoards,
     OutOfBounds,
     ShotExit,
+    // islands can't be shot at
+    Island,
 }
 #[derive(Debug, PartialEq, Eq)]
 pub enum PlacementError {
     ShipOverlap,
+    // only under the no touch rule
+    ShipsTouch,
     OutOfBounds,
     PlacementExit,
+    TooManyShips,
+    FleetDoesNotFit,
+    // the cell is an island or a mine
+    Terrain,
+}
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveError {
+    // the game is not played with moving ships
+    NotAllowed,
+    NoShip,
+    // only ships that weren't hit yet can move
+    Damaged,
+}
+#[derive(Debug, PartialEq, Eq)]
+pub enum RepairError {
+    // the game is not played with repairs
+    NotAllowed,
+    // only a part that was destroyed on a ship still afloat can be repaired
+    NotHit,
+    AlreadyRepaired,
 }
 impl Display for PlacementError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,56 +76,308 @@ impl Display for BoardError {
 }
 impl Error for BoardError {}
 
-pub const WIDTH: usize = 10;
-pub const HEIGHT: usize = 10;
+// the places in the fleet, the biggest ships first as they are the hardest to fit, a placement
+// that goes by it renumbers the ships once it's done
+pub fn biggest_first(fleet: &[ShipBlueprint]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fleet.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(fleet[i].parts.len()));
+    order
+}
+
+pub const DEFAULT_WIDTH: usize = 10;
+pub const DEFAULT_HEIGHT: usize = 10;
+// how many ship positions fit_fleet tries before it gives up on a fleet
+const FIT_BUDGET: usize = 1_000_000;
+
+// What came of looking for a way to place a whole fleet.
+pub enum Fit {
+    Placed(PlayerBoard),
+    // every position of every ship was tried
+    Impossible,
+    // the search ran out of tries before finding out, the fleet may still fit
+    GaveUp,
+}
 
 #[derive(Debug)]
 pub enum ShotResult<'a> {
+    // a part was destroyed
     Hit,
+    // an armored part took the hit and holds
+    Damaged,
     Miss,
     ShipSunk(&'a Vec<Point>),
+    // the last part of a decoy was destroyed, it is shown for what it is
+    Decoy(&'a Vec<Point>),
+    // a mine went off, the shooter's own fleet takes the blast
+    Mine,
+}
+
+// an owned ShotResult, kept in records and sent over the wire
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShotOutcome {
+    Hit,
+    Damaged,
+    Miss,
+    Sunk { parts: Vec<Point> },
+    Decoy { parts: Vec<Point> },
+    Mine,
+}
+impl From<&ShotResult<'_>> for ShotOutcome {
+    fn from(shot: &ShotResult) -> Self {
+        match shot {
+            ShotResult::Hit => Self::Hit,
+            ShotResult::Damaged => Self::Damaged,
+            ShotResult::Miss => Self::Miss,
+            ShotResult::ShipSunk(parts) => Self::Sunk {
+                parts: parts.to_vec(),
+            },
+            ShotResult::Decoy(parts) => Self::Decoy {
+                parts: parts.to_vec(),
+            },
+            ShotResult::Mine => Self::Mine,
+        }
+    }
+}
+impl ShotOutcome {
+    pub fn as_shot(&self) -> ShotResult<'_> {
+        match self {
+            Self::Hit => ShotResult::Hit,
+            Self::Damaged => ShotResult::Damaged,
+            Self::Miss => ShotResult::Miss,
+            Self::Sunk { parts } => ShotResult::ShipSunk(parts),
+            Self::Decoy { parts } => ShotResult::Decoy(parts),
+            Self::Mine => ShotResult::Mine,
+        }
+    }
 }
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ViewBoard {
-    grid: [[Tile; WIDTH]; HEIGHT],
+    grid: Vec<Vec<Tile>>,
+    // the cells around a sunk ship can't hold another one, they are marked as misses
+    #[serde(default)]
+    no_touch: bool,
 }
 impl ViewBoard {
-    pub fn new() -> Self {
+    pub fn new(width: usize, height: usize) -> Self {
         Self {
-            grid: [[Tile::Hidden; WIDTH]; HEIGHT],
+            grid: vec![vec![Tile::Hidden; width]; height],
+            no_touch: false,
+        }
+    }
+    pub fn with_no_touch(mut self, no_touch: bool) -> Self {
+        self.no_touch = no_touch;
+        self
+    }
+    // the islands are in sight from the start, the mines are not
+    pub fn with_map(mut self, map: &Map) -> Self {
+        for p in &map.islands {
+            self.grid[p.y][p.x] = Tile::Island;
         }
+        self
     }
-    pub fn get_grid(&self) -> &[[Tile; WIDTH]; HEIGHT] {
+    pub fn get_grid(&self) -> &[Vec<Tile>] {
         &self.grid
     }
+    pub fn width(&self) -> usize {
+        self.grid.first().map_or(0, |row| row.len())
+    }
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+    pub fn tile(&self, p: Point) -> Option<Tile> {
+        self.grid.get(p.y)?.get(p.x).copied()
+    }
+    // how many cells can still be shot at, a damaged or repaired part takes another shot
+    pub fn targets(&self) -> usize {
+        self.grid
+            .iter()
+            .flatten()
+            .filter(|tile| tile.is_target())
+            .count()
+    }
     pub fn register_shot(&mut self, shot: ShotResult, p: Point) -> Result<(), BoardError> {
-        if p.x >= WIDTH || p.y >= HEIGHT {
+        let parts = match &shot {
+            ShotResult::ShipSunk(points) | ShotResult::Decoy(points) => points.as_slice(),
+            _ => &[],
+        };
+        if parts.iter().chain([&p]).any(|q| self.tile(*q).is_none()) {
             return Err(BoardError::ShotRegisterError);
         }
         let tile = &mut self.grid[p.y][p.x];
         match shot {
             ShotResult::Hit => *tile = Tile::Hit,
+            ShotResult::Damaged => *tile = Tile::Damaged,
             ShotResult::Miss => *tile = Tile::Miss,
-            ShotResult::ShipSunk(points) => points
-                .iter()
-                .for_each(|p| self.grid[p.y][p.x] = Tile::SunkenShip),
+            ShotResult::Mine => *tile = Tile::Exploded,
+            ShotResult::ShipSunk(points) | ShotResult::Decoy(points) => {
+                let sunk = match shot {
+                    ShotResult::Decoy(_) => Tile::Decoy,
+                    _ => Tile::SunkenShip,
+                };
+                points.iter().for_each(|p| self.grid[p.y][p.x] = sunk);
+                if self.no_touch {
+                    for around in points.iter().flat_map(|p| p.neighbours()) {
+                        if self.tile(around).is_some_and(Tile::is_unknown) {
+                            self.grid[around.y][around.x] = Tile::Miss;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    // the opponent patched up the hit at p, it's no longer a confirmed hit
+    pub fn register_repair(&mut self, p: Point) -> Result<(), BoardError> {
+        match self.tile(p) {
+            Some(Tile::Hit) => {
+                self.grid[p.y][p.x] = Tile::Repaired;
+                Ok(())
+            }
+            Some(_) => Err(BoardError::ShipRepairError(RepairError::NotHit)),
+            None => Err(BoardError::ShotRegisterError),
+        }
+    }
+    // the opponent moved one of their ships, anything that showed where they weren't may be wrong
+    pub fn register_move(&mut self) {
+        for tile in self.grid.iter_mut().flatten() {
+            if matches!(tile, Tile::Miss | Tile::Scanned | Tile::Pinged(_)) {
+                *tile = Tile::Stale;
+            }
+        }
+    }
+    pub fn register_strike(
+        &mut self,
+        weapon: Weapon,
+        p: Point,
+        strike: &Strike,
+    ) -> Result<(), BoardError> {
+        if p.x >= self.width() || p.y >= self.height() {
+            return Err(BoardError::ShotRegisterError);
+        }
+        match strike {
+            Strike::Shots { results } => {
+                for (at, result) in results {
+                    self.register_shot(result.as_shot(), *at)?;
+                }
+            }
+            // nothing is there, the whole area is as good as shot
+            Strike::Ping { count: 0 } => {
+                for q in weapon.area(p, self.width(), self.height()) {
+                    if self.grid[q.y][q.x].is_unknown() {
+                        self.grid[q.y][q.x] = Tile::Miss;
+                    }
+                }
+            }
+            Strike::Ping { count } => {
+                for q in weapon.area(p, self.width(), self.height()) {
+                    if self.grid[q.y][q.x] == Tile::Hidden {
+                        self.grid[q.y][q.x] = Tile::Scanned;
+                    }
+                }
+                if self.grid[p.y][p.x].is_unknown() {
+                    self.grid[p.y][p.x] = Tile::Pinged(*count as u8);
+                }
+            }
         }
         Ok(())
     }
 }
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PlayerBoard {
-    grid: [[Tile; WIDTH]; HEIGHT],
+    grid: Vec<Vec<Tile>>,
+    width: usize,
+    height: usize,
+    #[serde(with = "ships_by_id")]
     pub ships: HashMap<u8, Ship>,
+    #[serde(default)]
+    no_touch: bool,
+    // the owner may move undamaged ships instead of firing
+    #[serde(default)]
+    moving: bool,
+    // the owner may repair a hit instead of firing
+    #[serde(default)]
+    repairing: bool,
+}
+// Ships are written as a list, integer map keys don't survive every serde format.
+mod ships_by_id {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::game::ship::Ship;
+
+    pub fn serialize<S: Serializer>(ships: &HashMap<u8, Ship>, s: S) -> Result<S::Ok, S::Error> {
+        let mut ships: Vec<&Ship> = ships.values().collect();
+        ships.sort_by_key(|ship| ship.id);
+        s.collect_seq(ships)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<u8, Ship>, D::Error> {
+        let ships = Vec::<Ship>::deserialize(d)?;
+        Ok(ships.into_iter().map(|ship| (ship.id, ship)).collect())
+    }
 }
 impl PlayerBoard {
-    pub fn new() -> Self {
+    pub fn new(width: usize, height: usize) -> Self {
         Self {
-            grid: [[Tile::Empty; WIDTH]; HEIGHT],
+            grid: vec![vec![Tile::Empty; width]; height],
+            width,
+            height,
             ships: HashMap::new(),
+            no_touch: false,
+            moving: false,
+            repairing: false,
+        }
+    }
+    pub fn with_no_touch(mut self, no_touch: bool) -> Self {
+        self.no_touch = no_touch;
+        self
+    }
+    pub fn with_moving(mut self, moving: bool) -> Self {
+        self.moving = moving;
+        self
+    }
+    pub fn allows_moves(&self) -> bool {
+        self.moving
+    }
+    pub fn with_repairing(mut self, repairing: bool) -> Self {
+        self.repairing = repairing;
+        self
+    }
+    pub fn allows_repairs(&self) -> bool {
+        self.repairing
+    }
+    pub fn with_map(mut self, map: &Map) -> Self {
+        for p in &map.islands {
+            self.grid[p.y][p.x] = Tile::Island;
+        }
+        for p in &map.mines {
+            self.grid[p.y][p.x] = Tile::Mine;
         }
+        self
     }
-    pub fn get_grid(&self) -> &[[Tile; WIDTH]; HEIGHT] {
+    pub fn get_grid(&self) -> &[Vec<Tile>] {
         &self.grid
     }
+    // empty cells that no ship may be placed on because of the no touch rule
+    pub fn is_blocked(&self, p: Point) -> bool {
+        self.no_touch
+            && self.grid[p.y][p.x] == Tile::Empty
+            && p.neighbours().any(|n| self.is_ship_part(n))
+    }
+    // any part of a ship, hit or not
+    fn is_ship_part(&self, p: Point) -> bool {
+        matches!(
+            self.grid.get(p.y).and_then(|row| row.get(p.x)),
+            Some(Tile::Ship(_) | Tile::Hit | Tile::Damaged | Tile::SunkenShip | Tile::Decoy)
+        )
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
     pub fn can_place_ship(
         &self,
         blueprint: &ShipBlueprint,
@@ -105,17 +389,112 @@ impl PlayerBoard {
             let (x, y) = (point.x + pos.x, point.y + pos.y);
 
             // TODO: Implement checking for rotated ships
-            if !Self::is_point_valid(Point { x, y }) {
+            if !self.is_point_valid(Point { x, y }) {
                 return Err(BoardError::ShipPlacementError(PlacementError::OutOfBounds));
             }
+            if matches!(self.grid[y][x], Tile::Island | Tile::Mine) {
+                return Err(BoardError::ShipPlacementError(PlacementError::Terrain));
+            }
             if self.grid[y][x] != Tile::Empty {
                 return Err(BoardError::ShipPlacementError(PlacementError::ShipOverlap));
             }
+            if self.is_blocked(Point { x, y }) {
+                return Err(BoardError::ShipPlacementError(PlacementError::ShipsTouch));
+            }
         }
         Ok(rotated)
     }
-    pub fn is_point_valid(p: Point) -> bool {
-        p.x < WIDTH && p.y < HEIGHT
+    // whether the ship fits anywhere on the board, in any rotation
+    pub fn has_room_for(&self, blueprint: &ShipBlueprint) -> bool {
+        let mut rotation = Rotation::None;
+        loop {
+            let mut positions =
+                (0..self.height).flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)));
+            if positions.any(|pos| self.can_place_ship(blueprint, pos, rotation).is_ok()) {
+                return true;
+            }
+            rotation = rotation.next();
+            if rotation == Rotation::None {
+                return false;
+            }
+        }
+    }
+    // the fleet placed the first way it fits, every position of every ship is tried in turn
+    pub fn fit_fleet(&self, fleet: &[ShipBlueprint]) -> Fit {
+        self.fit_within(fleet, FIT_BUDGET)
+    }
+    // the same search, trying at most budget ship positions
+    fn fit_within(&self, fleet: &[ShipBlueprint], mut budget: usize) -> Fit {
+        let order = biggest_first(fleet);
+        let ships: Vec<&ShipBlueprint> = order.iter().map(|&i| &fleet[i]).collect();
+        let mut board = self.clone();
+        match board.fit(&ships, &mut budget) {
+            true => {
+                board.renumber(&order);
+                Fit::Placed(board)
+            }
+            false if budget == 0 => Fit::GaveUp,
+            false => Fit::Impossible,
+        }
+    }
+    fn fit(&mut self, ships: &[&ShipBlueprint], budget: &mut usize) -> bool {
+        let Some((ship, rest)) = ships.split_first() else {
+            return true;
+        };
+        let mut rotation = Rotation::None;
+        loop {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if *budget == 0 {
+                        return false;
+                    }
+                    *budget -= 1;
+                    let pos = Point::new(x, y);
+                    if self.can_place_ship(ship, pos, rotation).is_err() {
+                        continue;
+                    }
+                    if self.place_ship(ship, pos, rotation).is_err() {
+                        continue;
+                    }
+                    if self.fit(rest, budget) {
+                        return true;
+                    }
+                    self.remove_last_ship();
+                }
+            }
+            rotation = rotation.next();
+            if rotation == Rotation::None {
+                return false;
+            }
+        }
+    }
+    // the ship placed as the k-th gets the id order[k], so ids follow the fleet again after its
+    // ships were placed out of order
+    pub fn renumber(&mut self, order: &[usize]) {
+        let mut ships = HashMap::new();
+        for (old, mut ship) in self.ships.drain() {
+            let id = order.get(old as usize).map_or(old, |&new| new as u8);
+            ship.id = id;
+            for p in &ship.parts {
+                self.grid[p.y][p.x] = Tile::Ship(id);
+            }
+            ships.insert(id, ship);
+        }
+        self.ships = ships;
+    }
+    // takes back the ship placed last, ships only ever go on empty cells
+    fn remove_last_ship(&mut self) {
+        let Some(id) = self.ships.len().checked_sub(1) else {
+            return;
+        };
+        if let Some(ship) = self.ships.remove(&(id as u8)) {
+            for p in ship.parts {
+                self.grid[p.y][p.x] = Tile::Empty;
+            }
+        }
+    }
+    pub fn is_point_valid(&self, p: Point) -> bool {
+        p.x < self.width && p.y < self.height
     }
     pub fn place_ship(
         &mut self,
@@ -130,9 +509,15 @@ impl PlayerBoard {
             .map(|p| Point::new(pos.x + p.x, pos.y + p.y))
             .collect();
 
+        // ids only have to be unique on this board
+        let Ok(id) = u8::try_from(self.ships.len()) else {
+            return Err(BoardError::ShipPlacementError(PlacementError::TooManyShips));
+        };
         // TODO: Optimize this so there is no clone
         // Maybe use Rc? i dont know anything better xdd
-        let s = Ship::new(points.clone(), blueprint.name.clone());
+        let s = Ship::new(id, points.clone(), blueprint.name.clone())
+            .with_hp(blueprint.hp())
+            .with_decoy(blueprint.decoy);
         // create and insert the tiles into the board
         for p in points {
             self.grid[p.y][p.x] = Tile::Ship(s.id)
@@ -141,54 +526,261 @@ impl PlayerBoard {
         Ok(())
     }
     // return some state enum or tile whatever
-    pub fn process_shot(&mut self, p: Point) -> Result<ShotResult, BoardError> {
-        if !Self::is_point_valid(p) {
+    pub fn process_shot(&mut self, p: Point) -> Result<ShotResult<'_>, BoardError> {
+        if !self.is_point_valid(p) {
             return Err(BoardError::Shot(ShotError::OutOfBounds));
         }
         match self.grid[p.y][p.x] {
-            Tile::Ship(id) => {
-                let ship: &mut Ship = self.ships.get_mut(&id).expect("ship not exists");
-                let Some(ship_parts) = ship.hit() else {
-                    self.grid[p.y][p.x] = Tile::Hit;
-                    return Ok(ShotResult::Hit);
-                };
-                ship_parts
-                    .iter()
-                    .for_each(|point| self.grid[point.y][point.x] = Tile::SunkenShip);
-                Ok(ShotResult::ShipSunk(ship_parts))
+            Tile::Ship(_) | Tile::Damaged => {
+                let ship: &mut Ship = self
+                    .ships
+                    .values_mut()
+                    .find(|ship| ship.parts.contains(&p))
+                    .expect("ship not exists");
+                let decoy = ship.decoy;
+                match ship.hit(p) {
+                    Damage::Dented => {
+                        self.grid[p.y][p.x] = Tile::Damaged;
+                        Ok(ShotResult::Damaged)
+                    }
+                    Damage::Destroyed => {
+                        self.grid[p.y][p.x] = Tile::Hit;
+                        Ok(ShotResult::Hit)
+                    }
+                    Damage::Sunk(ship_parts) if decoy => {
+                        ship_parts
+                            .iter()
+                            .for_each(|point| self.grid[point.y][point.x] = Tile::Decoy);
+                        Ok(ShotResult::Decoy(ship_parts))
+                    }
+                    Damage::Sunk(ship_parts) => {
+                        ship_parts
+                            .iter()
+                            .for_each(|point| self.grid[point.y][point.x] = Tile::SunkenShip);
+                        Ok(ShotResult::ShipSunk(ship_parts))
+                    }
+                }
             }
             Tile::Empty => {
                 self.grid[p.y][p.x] = Tile::Miss;
                 Ok(ShotResult::Miss)
             }
+            Tile::Mine => {
+                self.grid[p.y][p.x] = Tile::Exploded;
+                Ok(ShotResult::Mine)
+            }
+            Tile::Island => Err(BoardError::Shot(ShotError::Island)),
+            Tile::Exploded => Err(BoardError::Shot(ShotError::AlreadyShot)),
 
-            Tile::SunkenShip => Err(BoardError::Shot(ShotError::AlreadyShot)),
+            Tile::SunkenShip | Tile::Decoy => Err(BoardError::Shot(ShotError::AlreadyShot)),
             Tile::Hit => Err(BoardError::Shot(ShotError::AlreadyShot)),
             Tile::Miss => Err(BoardError::Shot(ShotError::AlreadyShot)),
-            Tile::Hidden => Err(BoardError::Shot(ShotError::HiddenDoesntExistOnPlayerBoards)),
+            Tile::Hidden | Tile::Scanned | Tile::Pinged(_) | Tile::Stale | Tile::Repaired => {
+                Err(BoardError::Shot(ShotError::HiddenDoesntExistOnPlayerBoards))
+            }
         }
     }
+    // a special attack, the shots it fires are resolved one by one like normal shots
+    pub fn process_strike(&mut self, weapon: Weapon, p: Point) -> Result<Strike, BoardError> {
+        if !self.is_point_valid(p) {
+            return Err(BoardError::Shot(ShotError::OutOfBounds));
+        }
+        let area = weapon.area(p, self.width, self.height);
+        if weapon == Weapon::Radar {
+            let count = area
+                .iter()
+                .filter(|q| {
+                    matches!(
+                        self.grid[q.y][q.x],
+                        Tile::Ship(_) | Tile::Hit | Tile::Damaged | Tile::SunkenShip | Tile::Decoy
+                    )
+                })
+                .count();
+            return Ok(Strike::Ping { count });
+        }
+        // cells that were shot at before are flown over, armored parts that still hold are not
+        let fresh: Vec<Point> = area
+            .into_iter()
+            .filter(|q| {
+                matches!(
+                    self.grid[q.y][q.x],
+                    Tile::Ship(_) | Tile::Damaged | Tile::Empty | Tile::Mine
+                )
+            })
+            .collect();
+        let mut results = Vec::new();
+        for q in fresh {
+            let result = ShotOutcome::from(&self.process_shot(q)?);
+            let stop = weapon == Weapon::Torpedo && result != ShotOutcome::Miss;
+            results.push((q, result));
+            if stop {
+                break;
+            }
+        }
+        if results.is_empty() {
+            return Err(BoardError::Shot(ShotError::AlreadyShot));
+        }
+        Ok(Strike::Shots { results })
+    }
+    // a mine set off at p by this board's owner hits the nearest part of their own fleet that can
+    // still be hit, the first of the closest row by row, None once every ship is gone
+    pub fn process_blast(&mut self, p: Point) -> Option<(Point, ShotOutcome)> {
+        let distance = |q: &Point| p.x.abs_diff(q.x).pow(2) + p.y.abs_diff(q.y).pow(2);
+        let target = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)))
+            .filter(|q| matches!(self.grid[q.y][q.x], Tile::Ship(_) | Tile::Damaged))
+            .min_by_key(distance)?;
+        let result = ShotOutcome::from(&self.process_shot(target).ok()?);
+        Some((target, result))
+    }
+    // where the undamaged ship with a part at p would end up, with its id
+    pub fn can_move_ship(
+        &self,
+        at: Point,
+        maneuver: Maneuver,
+    ) -> Result<(u8, Vec<Point>), BoardError> {
+        if !self.moving {
+            return Err(BoardError::ShipMoveError(MoveError::NotAllowed));
+        }
+        let Some(ship) = self.ships.values().find(|ship| ship.parts.contains(&at)) else {
+            return Err(BoardError::ShipMoveError(MoveError::NoShip));
+        };
+        let own = Tile::Ship(ship.id);
+        // a repaired ship is still too battered to sail
+        if !ship.repaired.is_empty() || ship.parts.iter().any(|p| self.grid[p.y][p.x] != own) {
+            return Err(BoardError::ShipMoveError(MoveError::Damaged));
+        }
+        let Some(parts) = maneuver.apply(&ship.parts) else {
+            return Err(BoardError::ShipPlacementError(PlacementError::OutOfBounds));
+        };
+        for p in &parts {
+            if !self.is_point_valid(*p) {
+                return Err(BoardError::ShipPlacementError(PlacementError::OutOfBounds));
+            }
+            // a ship sails over the misses, they are forgotten once it moved
+            match self.grid[p.y][p.x] {
+                Tile::Empty | Tile::Miss => {}
+                tile if tile == own => {}
+                Tile::Island | Tile::Mine | Tile::Exploded => {
+                    return Err(BoardError::ShipPlacementError(PlacementError::Terrain));
+                }
+                _ => return Err(BoardError::ShipPlacementError(PlacementError::ShipOverlap)),
+            }
+            let touches = p
+                .neighbours()
+                .any(|n| self.is_ship_part(n) && !ship.parts.contains(&n));
+            if self.no_touch && touches {
+                return Err(BoardError::ShipPlacementError(PlacementError::ShipsTouch));
+            }
+        }
+        Ok((ship.id, parts))
+    }
+    // moves the undamaged ship with a part at p, returns its id, which is its place in the fleet
+    pub fn move_ship(&mut self, at: Point, maneuver: Maneuver) -> Result<usize, BoardError> {
+        let (id, parts) = self.can_move_ship(at, maneuver)?;
+        // the opponent's misses may not be misses anymore, they can be shot at again
+        for tile in self.grid.iter_mut().flatten() {
+            if *tile == Tile::Miss || *tile == Tile::Ship(id) {
+                *tile = Tile::Empty;
+            }
+        }
+        for p in &parts {
+            self.grid[p.y][p.x] = Tile::Ship(id);
+        }
+        let ship = self.ships.get_mut(&id).expect("the ship was just found");
+        ship.move_to(parts);
+        // ids follow the order of the fleet, see renumber
+        Ok(id as usize)
+    }
+    // the id of the ship whose destroyed part at p could be repaired
+    pub fn can_repair_ship(&self, p: Point) -> Result<u8, BoardError> {
+        if !self.repairing {
+            return Err(BoardError::ShipRepairError(RepairError::NotAllowed));
+        }
+        // a sunk ship shows as sunken, a hit is always on a ship still afloat
+        if !self.is_point_valid(p) || self.grid[p.y][p.x] != Tile::Hit {
+            return Err(BoardError::ShipRepairError(RepairError::NotHit));
+        }
+        let ship = self
+            .ships
+            .values()
+            .find(|ship| ship.parts.contains(&p))
+            .expect("hits are on ships");
+        if ship.repaired.contains(&p) {
+            return Err(BoardError::ShipRepairError(RepairError::AlreadyRepaired));
+        }
+        Ok(ship.id)
+    }
+    // spends the turn patching up the destroyed part at p
+    pub fn repair_ship(&mut self, p: Point) -> Result<(), BoardError> {
+        let id = self.can_repair_ship(p)?;
+        let ship = self.ships.get_mut(&id).expect("the ship was just found");
+        ship.repair(p);
+        self.grid[p.y][p.x] = Tile::Ship(id);
+        Ok(())
+    }
+    // the hits that could be repaired right now
+    pub fn repairable(&self) -> Vec<Point> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)))
+            .filter(|p| self.can_repair_ship(*p).is_ok())
+            .collect()
+    }
+    // the cells of every ship, ordered by ship id
+    pub fn fleet(&self) -> Vec<Vec<Point>> {
+        let mut ids: Vec<&u8> = self.ships.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| self.ships[id].parts.clone())
+            .collect()
+    }
+    // the positions in fleet() of the decoys
+    pub fn decoys(&self) -> Vec<usize> {
+        let mut ids: Vec<&u8> = self.ships.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .enumerate()
+            .filter(|(_, id)| self.ships[id].decoy)
+            .map(|(i, _)| i)
+            .collect()
+    }
+    // decoys don't have to be sunk
     pub fn is_game_over(&self) -> bool {
-        self.ships.iter().all(|(_, ship)| !ship.is_alive())
+        self.ships
+            .values()
+            .all(|ship| ship.decoy || !ship.is_alive())
+    }
+    pub fn ships_afloat(&self) -> usize {
+        self.ships
+            .values()
+            .filter(|ship| !ship.decoy && ship.is_alive())
+            .count()
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::game::{
-        player_board::{BoardError, PlacementError, PlayerBoard, ShotResult},
+        config::Map,
+        player_board::ShotOutcome,
+        player_board::{
+            BoardError, Fit, MoveError, PlacementError, PlayerBoard, RepairError, ShotError,
+            ShotResult, ViewBoard,
+        },
         point::Point,
         rotation::Rotation,
-        ship::ShipBlueprint,
+        ship::{Maneuver, ShipBlueprint},
+        tile::Tile,
+        weapon::{Strike, Weapon},
     };
     fn default_setup() -> (PlayerBoard, ShipBlueprint) {
-        let board: PlayerBoard = PlayerBoard::new();
+        let board: PlayerBoard = PlayerBoard::new(10, 10);
         let blueprint: ShipBlueprint =
             ShipBlueprint::new(vec![Point::new(0, 0)], String::from("dot"));
         (board, blueprint)
     }
     fn big_ship_setup() -> (PlayerBoard, ShipBlueprint) {
-        let board = PlayerBoard::new();
+        let board = PlayerBoard::new(10, 10);
         let bp = ShipBlueprint::new(
             vec![
                 Point::new(0, 0),
@@ -275,5 +867,436 @@ mod test {
             .place_ship(&sm, p, Rotation::None)
             .expect("should be ok to place");
         let res = board.process_shot(p).expect("should be ok");
+        assert!(matches!(res, ShotResult::ShipSunk(_)));
+    }
+    #[test]
+    fn board_size_is_not_fixed() {
+        let mut board = PlayerBoard::new(15, 6);
+        let bp = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+            String::from("line"),
+        );
+        assert!(
+            board
+                .place_ship(&bp, Point::new(12, 5), Rotation::None)
+                .is_ok()
+        );
+        assert_eq!(
+            board
+                .place_ship(&bp, Point::new(0, 5), Rotation::Horizontal)
+                .expect_err("should be err"),
+            BoardError::ShipPlacementError(PlacementError::OutOfBounds)
+        );
+        assert_eq!(
+            board
+                .process_shot(Point::new(15, 0))
+                .expect_err("should be err"),
+            BoardError::Shot(ShotError::OutOfBounds)
+        );
+        assert!(matches!(
+            board.process_shot(Point::new(14, 5)),
+            Ok(ShotResult::Hit)
+        ));
+    }
+    #[test]
+    fn no_touch_keeps_ships_apart() {
+        let (board, blueprint) = default_setup();
+        let mut board = board.with_no_touch(true);
+        assert!(
+            board
+                .place_ship(&blueprint, Point::new(4, 4), Rotation::None)
+                .is_ok()
+        );
+        assert_eq!(
+            board
+                .place_ship(&blueprint, Point::new(5, 5), Rotation::None)
+                .expect_err("diagonal neighbour should be err"),
+            BoardError::ShipPlacementError(PlacementError::ShipsTouch)
+        );
+        assert!(board.is_blocked(Point::new(3, 4)));
+        assert!(!board.is_blocked(Point::new(6, 4)));
+        assert!(
+            board
+                .place_ship(&blueprint, Point::new(6, 4), Rotation::None)
+                .is_ok()
+        );
+    }
+    #[test]
+    fn no_touch_marks_cells_around_sunk_ship() {
+        let sunk = vec![Point::new(0, 0), Point::new(1, 0)];
+        let mut view = ViewBoard::new(10, 10).with_no_touch(true);
+        view.register_shot(ShotResult::Hit, Point::new(2, 1))
+            .expect("should be ok");
+        view.register_shot(ShotResult::ShipSunk(&sunk), Point::new(1, 0))
+            .expect("should be ok");
+        for p in [Point::new(2, 0), Point::new(0, 1), Point::new(1, 1)] {
+            assert_eq!(view.tile(p), Some(Tile::Miss));
+        }
+        // what was already known stays as it was
+        assert_eq!(view.tile(Point::new(2, 1)), Some(Tile::Hit));
+        assert_eq!(view.tile(Point::new(3, 0)), Some(Tile::Hidden));
+
+        let mut view = ViewBoard::new(10, 10);
+        view.register_shot(ShotResult::ShipSunk(&sunk), Point::new(1, 0))
+            .expect("should be ok");
+        assert_eq!(view.tile(Point::new(2, 0)), Some(Tile::Hidden));
+    }
+    #[test]
+    fn special_weapons_are_resolved_by_the_board() {
+        let (mut board, bp) = big_ship_setup();
+        board
+            .place_ship(&bp, Point::new(5, 5), Rotation::None)
+            .expect("should be ok to place");
+        assert_eq!(
+            board.process_strike(Weapon::Radar, Point::new(5, 5)),
+            Ok(Strike::Ping { count: 4 })
+        );
+        // the torpedo crosses the water and stops at the ship
+        let Ok(Strike::Shots { results }) = board.process_strike(Weapon::Torpedo, Point::new(2, 5))
+        else {
+            panic!("torpedoes shoot");
+        };
+        let cells: Vec<Point> = results.iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            cells,
+            vec![
+                Point::new(2, 5),
+                Point::new(3, 5),
+                Point::new(4, 5),
+                Point::new(5, 5)
+            ]
+        );
+        assert_eq!(results[3].1, ShotOutcome::Hit);
+        // a second torpedo flies over what was already shot
+        let Ok(Strike::Shots { results }) = board.process_strike(Weapon::Torpedo, Point::new(2, 5))
+        else {
+            panic!("torpedoes shoot");
+        };
+        assert_eq!(results, vec![(Point::new(6, 5), ShotOutcome::Hit)]);
+        // the airstrike skips the cells shot before and sinks the ship
+        let Ok(Strike::Shots { results }) =
+            board.process_strike(Weapon::Airstrike, Point::new(5, 6))
+        else {
+            panic!("airstrikes shoot");
+        };
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[2].1, ShotOutcome::Sunk { .. }));
+        assert!(board.is_game_over());
+        assert_eq!(
+            board.process_strike(Weapon::Airstrike, Point::new(5, 6)),
+            Err(BoardError::Shot(ShotError::AlreadyShot))
+        );
+    }
+    #[test]
+    fn radar_pings_show_on_the_view_board() {
+        let mut view = ViewBoard::new(10, 10);
+        view.register_strike(Weapon::Radar, Point::new(1, 1), &Strike::Ping { count: 2 })
+            .expect("should be ok");
+        assert_eq!(view.tile(Point::new(1, 1)), Some(Tile::Pinged(2)));
+        assert_eq!(view.tile(Point::new(0, 2)), Some(Tile::Scanned));
+        assert_eq!(view.targets(), 100);
+        // nothing found means nothing is there
+        view.register_strike(Weapon::Radar, Point::new(8, 8), &Strike::Ping { count: 0 })
+            .expect("should be ok");
+        assert_eq!(view.tile(Point::new(9, 9)), Some(Tile::Miss));
+        assert_eq!(view.targets(), 91);
+    }
+    #[test]
+    fn armored_parts_take_more_hits() {
+        let mut board = PlayerBoard::new(10, 10);
+        let bp = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0)],
+            String::from("citadel"),
+        )
+        .with_hp(vec![2, 1]);
+        board
+            .place_ship(&bp, Point::new(3, 3), Rotation::None)
+            .expect("should be ok to place");
+        let citadel = Point::new(3, 3);
+        assert!(matches!(
+            board.process_shot(citadel),
+            Ok(ShotResult::Damaged)
+        ));
+        assert_eq!(board.get_grid()[3][3], Tile::Damaged);
+        assert!(matches!(board.process_shot(citadel), Ok(ShotResult::Hit)));
+        assert_eq!(
+            board.process_shot(citadel).expect_err("should be err"),
+            BoardError::Shot(ShotError::AlreadyShot)
+        );
+        assert!(matches!(
+            board.process_shot(Point::new(4, 3)),
+            Ok(ShotResult::ShipSunk(_))
+        ));
+
+        let mut view = ViewBoard::new(10, 10);
+        view.register_shot(ShotResult::Damaged, citadel)
+            .expect("should be ok");
+        assert_eq!(view.tile(citadel), Some(Tile::Damaged));
+        // the part that held can still be shot at
+        assert_eq!(view.targets(), 100);
+    }
+    #[test]
+    fn terrain_blocks_ships_and_mines_blast_back() {
+        let map = Map {
+            islands: vec![Point::new(2, 0)],
+            mines: vec![Point::new(5, 5)],
+        };
+        let (board, blueprint) = big_ship_setup();
+        let mut board = board.with_map(&map);
+        for pos in [Point::new(1, 0), Point::new(4, 4)] {
+            assert_eq!(
+                board.can_place_ship(&blueprint, pos, Rotation::None),
+                Err(BoardError::ShipPlacementError(PlacementError::Terrain))
+            );
+        }
+        board
+            .place_ship(&blueprint, Point::new(0, 0), Rotation::None)
+            .expect("should be ok to place");
+        assert_eq!(
+            board
+                .process_shot(Point::new(2, 0))
+                .expect_err("should be err"),
+            BoardError::Shot(ShotError::Island)
+        );
+        assert!(matches!(
+            board.process_shot(Point::new(5, 5)),
+            Ok(ShotResult::Mine)
+        ));
+        assert_eq!(
+            board
+                .process_shot(Point::new(5, 5))
+                .expect_err("should be err"),
+            BoardError::Shot(ShotError::AlreadyShot)
+        );
+        // the closest part to a mine set off at the far corner is the lower right one
+        assert_eq!(
+            board.process_blast(Point::new(9, 9)),
+            Some((Point::new(1, 1), ShotOutcome::Hit))
+        );
+        // a tie goes to the one that comes first row by row
+        assert_eq!(
+            board.process_blast(Point::new(9, 9)),
+            Some((Point::new(1, 0), ShotOutcome::Hit))
+        );
+
+        let mut view = ViewBoard::new(10, 10).with_map(&map);
+        assert_eq!(view.tile(Point::new(2, 0)), Some(Tile::Island));
+        assert_eq!(view.tile(Point::new(5, 5)), Some(Tile::Hidden));
+        assert_eq!(view.targets(), 99);
+        view.register_shot(ShotResult::Mine, Point::new(5, 5))
+            .expect("should be ok");
+        assert_eq!(view.tile(Point::new(5, 5)), Some(Tile::Exploded));
+    }
+    #[test]
+    fn undamaged_ships_can_move() {
+        let line = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+            String::from("line"),
+        );
+        let mut board = PlayerBoard::new(10, 10);
+        board
+            .place_ship(&line, Point::new(0, 0), Rotation::None)
+            .expect("should be ok to place");
+        assert_eq!(
+            board.move_ship(Point::new(0, 0), Maneuver::Down),
+            Err(BoardError::ShipMoveError(MoveError::NotAllowed))
+        );
+        let mut board = board.with_moving(true);
+        assert_eq!(
+            board.move_ship(Point::new(5, 5), Maneuver::Down),
+            Err(BoardError::ShipMoveError(MoveError::NoShip))
+        );
+        assert_eq!(
+            board.move_ship(Point::new(0, 0), Maneuver::Up),
+            Err(BoardError::ShipPlacementError(PlacementError::OutOfBounds))
+        );
+        // a miss is sailed over and forgotten, it can be shot at again
+        assert!(matches!(
+            board.process_shot(Point::new(1, 1)),
+            Ok(ShotResult::Miss)
+        ));
+        assert_eq!(board.move_ship(Point::new(2, 0), Maneuver::Down), Ok(0));
+        assert_eq!(
+            board.fleet(),
+            vec![vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1)]]
+        );
+        assert_eq!(board.get_grid()[0][0], Tile::Empty);
+        assert!(matches!(
+            board.process_shot(Point::new(1, 1)),
+            Ok(ShotResult::Hit)
+        ));
+        // a ship that took a hit stays where it is
+        assert_eq!(
+            board.move_ship(Point::new(0, 1), Maneuver::Down),
+            Err(BoardError::ShipMoveError(MoveError::Damaged))
+        );
+    }
+    #[test]
+    fn ships_rotate_around_their_corner() {
+        let line = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+            String::from("line"),
+        );
+        let mut board = PlayerBoard::new(10, 10).with_moving(true);
+        board
+            .place_ship(&line, Point::new(3, 3), Rotation::None)
+            .expect("should be ok to place");
+        board
+            .place_ship(&line, Point::new(3, 5), Rotation::None)
+            .expect("should be ok to place");
+        assert_eq!(
+            board.move_ship(Point::new(3, 3), Maneuver::Rotate),
+            Err(BoardError::ShipPlacementError(PlacementError::ShipOverlap))
+        );
+        assert_eq!(board.move_ship(Point::new(3, 5), Maneuver::Rotate), Ok(1));
+        assert_eq!(
+            board.fleet()[1],
+            vec![Point::new(3, 5), Point::new(3, 6), Point::new(3, 7)]
+        );
+    }
+    #[test]
+    fn moves_make_the_view_stale() {
+        let mut view = ViewBoard::new(10, 10);
+        view.register_shot(ShotResult::Miss, Point::new(0, 0))
+            .expect("should be ok");
+        view.register_shot(ShotResult::Hit, Point::new(1, 0))
+            .expect("should be ok");
+        view.register_move();
+        assert_eq!(view.tile(Point::new(0, 0)), Some(Tile::Stale));
+        assert_eq!(view.tile(Point::new(1, 0)), Some(Tile::Hit));
+        assert_eq!(view.targets(), 99);
+    }
+    #[test]
+    fn hits_can_be_repaired_once() {
+        let line = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+            String::from("line"),
+        );
+        let mut board = PlayerBoard::new(10, 10).with_moving(true);
+        board
+            .place_ship(&line, Point::new(0, 0), Rotation::None)
+            .expect("should be ok to place");
+        board.process_shot(Point::new(1, 0)).expect("should hit");
+        assert_eq!(
+            board.repair_ship(Point::new(1, 0)),
+            Err(BoardError::ShipRepairError(RepairError::NotAllowed))
+        );
+        let mut board = board.with_repairing(true);
+        assert_eq!(
+            board.repair_ship(Point::new(0, 0)),
+            Err(BoardError::ShipRepairError(RepairError::NotHit))
+        );
+        assert_eq!(board.repairable(), vec![Point::new(1, 0)]);
+        assert_eq!(board.repair_ship(Point::new(1, 0)), Ok(()));
+        assert_eq!(board.get_grid()[0][1], Tile::Ship(0));
+        // the ship is whole again but stays too battered to move
+        assert_eq!(
+            board.move_ship(Point::new(0, 0), Maneuver::Down),
+            Err(BoardError::ShipMoveError(MoveError::Damaged))
+        );
+        assert!(matches!(
+            board.process_shot(Point::new(1, 0)),
+            Ok(ShotResult::Hit)
+        ));
+        assert_eq!(
+            board.repair_ship(Point::new(1, 0)),
+            Err(BoardError::ShipRepairError(RepairError::AlreadyRepaired))
+        );
+
+        let mut view = ViewBoard::new(10, 10);
+        view.register_shot(ShotResult::Hit, Point::new(1, 0))
+            .expect("should be ok");
+        view.register_repair(Point::new(1, 0))
+            .expect("the cell was a hit");
+        assert_eq!(view.tile(Point::new(1, 0)), Some(Tile::Repaired));
+        assert!(Tile::Repaired.is_target());
+        assert!(view.register_repair(Point::new(2, 0)).is_err());
+    }
+    #[test]
+    fn decoys_dont_have_to_be_sunk() {
+        let (mut board, ship) = default_setup();
+        let decoy = ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(1, 0)],
+            String::from("dummy"),
+        )
+        .with_decoy(true);
+        board
+            .place_ship(&decoy, Point::new(0, 0), Rotation::None)
+            .expect("should be ok to place");
+        board
+            .place_ship(&ship, Point::new(5, 5), Rotation::None)
+            .expect("should be ok to place");
+        assert_eq!(board.decoys(), vec![0]);
+        assert_eq!(board.ships_afloat(), 1);
+        assert!(matches!(
+            board.process_shot(Point::new(0, 0)),
+            Ok(ShotResult::Hit)
+        ));
+        let res = board.process_shot(Point::new(1, 0)).expect("should hit");
+        let parts = match res {
+            ShotResult::Decoy(parts) => parts.clone(),
+            other => panic!("expected a decoy, got {other:?}"),
+        };
+        assert!(!board.is_game_over());
+        assert_eq!(board.get_grid()[0][1], Tile::Decoy);
+        assert_eq!(
+            board.process_shot(Point::new(1, 0)).err(),
+            Some(BoardError::Shot(ShotError::AlreadyShot))
+        );
+
+        let mut view = ViewBoard::new(10, 10).with_no_touch(true);
+        view.register_shot(ShotResult::Decoy(&parts), Point::new(1, 0))
+            .expect("should be in bounds");
+        assert_eq!(view.tile(Point::new(0, 0)), Some(Tile::Decoy));
+        assert_eq!(view.tile(Point::new(2, 0)), Some(Tile::Miss));
+
+        board.process_shot(Point::new(5, 5)).expect("should sink");
+        assert!(board.is_game_over());
+    }
+    #[test]
+    fn room_runs_out_for_a_ship() {
+        let line = |len: usize| {
+            ShipBlueprint::new(
+                (0..len).map(|x| Point::new(x, 0)).collect(),
+                String::from("line"),
+            )
+        };
+        let mut board = PlayerBoard::new(4, 4).with_no_touch(true);
+        // a line across the second row leaves only the last row for anything else
+        board
+            .place_ship(&line(4), Point::new(0, 1), Rotation::None)
+            .expect("should be ok to place");
+        assert!(board.has_room_for(&line(4)));
+        board
+            .place_ship(&line(4), Point::new(0, 3), Rotation::None)
+            .expect("should be ok to place");
+        assert!(!board.has_room_for(&line(1)));
+        // placed the other way around, both lines still fit
+        let Fit::Placed(fitted) = PlayerBoard::new(4, 4)
+            .with_no_touch(true)
+            .fit_fleet(&[line(4), line(4)])
+        else {
+            panic!("two lines fit on rows apart");
+        };
+        assert_eq!(fitted.fleet().len(), 2);
+        assert!(matches!(
+            PlayerBoard::new(4, 4)
+                .with_no_touch(true)
+                .fit_fleet(&vec![line(4); 3]),
+            Fit::Impossible
+        ));
+        // running out of tries says nothing about the fleet
+        assert!(matches!(
+            PlayerBoard::new(4, 4)
+                .with_no_touch(true)
+                .fit_within(&[line(4), line(4)], 3),
+            Fit::GaveUp
+        ));
+        // and every ship tried is taken back off the board
+        let empty = PlayerBoard::new(4, 4).with_no_touch(true);
+        let mut board = empty.clone();
+        assert!(!board.fit(&[&line(4), &line(4), &line(4)], &mut 10_000));
+        assert_eq!(board.get_grid(), empty.get_grid());
+        assert!(board.ships.is_empty());
     }
 }
@@ -1,21 +1,16 @@
-use std::error::Error;
-
-use crossterm::{
-    event::{KeyCode, KeyEvent},
-    style::{StyledContent, Stylize},
-};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Rect},
     style::{Color, Style},
     symbols::border,
     text::{Line, Text},
-    widgets::{Block, Row, Table, Widget},
+    widgets::{Block, Row, Table},
 };
 
-use crate::game::{cursor::Cursor, player_board::HEIGHT, tile::Tile};
+use crate::game::{cursor::Cursor, tile::Tile};
 use crate::game::{
-    player_board::{BoardError, PlayerBoard, WIDTH},
+    player_board::{BoardError, PlayerBoard},
     point::Point,
     rotation::Rotation,
     ship::ShipBlueprint,
@@ -25,16 +20,22 @@ pub struct BoardBuilder<'a> {
     rotation: Rotation,
     current_ship: ShipBlueprint,
     board: &'a PlayerBoard,
+    seed: Option<u64>,
 }
 impl<'a> BoardBuilder<'a> {
     pub fn new(board: &'a PlayerBoard, ship: &'a ShipBlueprint) -> Self {
         Self {
             board,
-            cursor: Cursor::new(0, 0, WIDTH, HEIGHT),
+            cursor: Cursor::new(0, 0, board.width(), board.height()),
             current_ship: ship.clone(),
             rotation: Rotation::None,
+            seed: None,
         }
     }
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
 
     pub fn handle_key(&mut self, key: KeyEvent) -> Result<Option<(Point, Rotation)>, BoardError> {
         match key.code {
@@ -77,8 +78,15 @@ impl<'a> BoardBuilder<'a> {
         }
     }
     pub fn render(&self, f: &mut Frame, rect: Rect) {
-        let title = Line::from(" Position your ships! ");
-        let block = Block::bordered().title(title).border_set(border::THICK);
+        let title = if self.board.has_room_for(&self.current_ship) {
+            Line::from(" Position your ships! esc to start over ")
+        } else {
+            Line::from(" No room left for this ship, esc to start over ")
+        };
+        let mut block = Block::bordered().title(title).border_set(border::THICK);
+        if let Some(seed) = self.seed {
+            block = block.title_bottom(Line::from(format!(" seed {seed} ")).right_aligned());
+        }
         let cursor = self.cursor.pos();
 
         let (rotated, can_place) =
@@ -114,13 +122,17 @@ impl<'a> BoardBuilder<'a> {
                                         .style(Style::new().bg(Color::Red));
                                 }
                             }
+                            if self.board.is_blocked(Point::new(x, y)) {
+                                return Text::from("·")
+                                    .style(Style::new().fg(Color::Gray).bg(Color::Blue));
+                            }
                             t.to_styled()
                         })
                         .collect::<Vec<Text>>(), // Collect as Text
                 )
             })
             .collect::<Vec<Row>>();
-        let widths = [Constraint::Length(1); WIDTH];
+        let widths = vec![Constraint::Length(1); self.board.width()];
         let table = Table::new(rows, widths).block(block).column_spacing(0);
         f.render_widget(table, rect);
     }
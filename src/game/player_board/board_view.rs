@@ -5,30 +5,40 @@ use ratatui::{
     style::{Color, Style},
     symbols::border,
     text::{Line, Text},
-    widgets::{Block, Row, StatefulWidget, Table},
+    widgets::{Block, Row, Table},
 };
 
 use crate::game::{
     cursor::Cursor,
-    player_board::{self, BoardError, HEIGHT, WIDTH},
+    player_board::{self, BoardError},
     point::Point,
     tile::Tile,
 };
 
 pub struct BoardView<'a> {
-    grid: &'a [[Tile; WIDTH]; HEIGHT],
+    grid: &'a [Vec<Tile>],
     cursor: Option<Cursor>,
     title: &'a str,
+    // cells picked but not fired at yet, like the targets of a salvo
+    marked: &'a [Point],
 }
 
 impl<'a> BoardView<'a> {
-    pub fn new(grid: &'a [[Tile; WIDTH]; HEIGHT], cursor: Option<Cursor>, title: &'a str) -> Self {
+    pub fn new(grid: &'a [Vec<Tile>], cursor: Option<Cursor>, title: &'a str) -> Self {
         Self {
             grid,
             cursor,
             title,
+            marked: &[],
         }
     }
+    pub fn with_marked(mut self, marked: &'a [Point]) -> Self {
+        self.marked = marked;
+        self
+    }
+    pub fn cursor_pos(&self) -> Option<Point> {
+        self.cursor.as_ref().map(|cursor| cursor.pos())
+    }
     pub fn render(&self, f: &mut Frame, rect: Rect) {
         let title: Line = Line::from(self.title);
         let block = Block::bordered().title(title).border_set(border::THICK);
@@ -42,10 +52,14 @@ impl<'a> BoardView<'a> {
                         .enumerate()
                         .map(|(x, t)| {
                             // TODO: Or just push it into the vector which will be alot more performant
-                            if let Some(cursor) = &self.cursor {
-                                if cursor.pos() == Point::new(x, y) {
-                                    return t.to_styled().style(Style::new().bg(Color::Yellow));
-                                }
+                            if let Some(cursor) = &self.cursor
+                                && cursor.pos() == Point::new(x, y)
+                            {
+                                return t.to_styled().style(Style::new().bg(Color::Yellow));
+                            }
+                            if self.marked.contains(&Point::new(x, y)) {
+                                return Text::from("+")
+                                    .style(Style::new().fg(Color::White).bg(Color::Magenta));
                             }
                             t.to_styled()
                         })
@@ -53,11 +67,12 @@ impl<'a> BoardView<'a> {
                 )
             })
             .collect::<Vec<Row>>();
-        let widths = [Constraint::Length(1); WIDTH];
+        let width = self.grid.first().map_or(0, |row| row.len());
+        let widths = vec![Constraint::Length(1); width];
         let table = Table::new(rows, widths).block(block).column_spacing(0);
         f.render_widget(table, rect);
     }
-    pub fn handle_key(&mut self, key: KeyEvent) -> Result<Option<(Point)>, BoardError> {
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<Option<Point>, BoardError> {
         if self.cursor.is_none() {
             return Ok(None);
         }
@@ -67,11 +82,7 @@ impl<'a> BoardView<'a> {
             KeyCode::Up => self.move_cursor(0, -1),
             KeyCode::Down => self.move_cursor(0, 1),
             KeyCode::Enter => return self.select(),
-            KeyCode::Esc => {
-                return Err(BoardError::ShipPlacementError(
-                    super::PlacementError::PlacementExit,
-                ));
-            }
+            KeyCode::Esc => return Err(BoardError::Shot(player_board::ShotError::ShotExit)),
             _ => {}
         }
         Ok(None)
@@ -83,7 +94,7 @@ impl<'a> BoardView<'a> {
             return;
         };
     }
-    fn select(&self) -> Result<Option<(Point)>, BoardError> {
+    fn select(&self) -> Result<Option<Point>, BoardError> {
         if let Some(cursor) = &self.cursor {
             Ok(Some(cursor.pos()))
         } else {
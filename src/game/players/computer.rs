@@ -1,60 +1,415 @@
-use rand::random_range;
+mod hunt_target;
+mod layout;
+mod probability;
+mod random;
+mod strategy;
 
-use crate::game::player_board::board_view::BoardView;
-use crate::game::player_board::{BoardError, HEIGHT, WIDTH};
-use crate::game::players::GamePlayer;
-use crate::game::rotation::Rotation;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::game::config::GameConfig;
+use crate::game::player_board::ShotOutcome;
+use crate::game::player_board::{BoardError, DEFAULT_HEIGHT, DEFAULT_WIDTH, ViewBoard};
+use crate::game::players::computer::hunt_target::HuntTarget;
+use crate::game::players::computer::probability::ProbabilityDensity;
+use crate::game::players::computer::random::RandomShots;
+use crate::game::players::{Action, GamePlayer};
+use crate::game::rng::{self, GameRng};
+use crate::game::save::{ComputerState, SaveError, SavedPlayer};
 use crate::game::{
     Setup,
     player_board::{PlayerBoard, ShotResult},
     point::Point,
-    ship::ShipBlueprint,
+    ship::Maneuver,
+    tile::Tile,
+    weapon::{Arsenal, Strike, Weapon},
 };
+pub use layout::Layout;
+pub use strategy::Strategy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    // shoots at random
+    Easy,
+    // hunts at random and finishes off ships it hits
+    #[default]
+    Normal,
+    // shoots where the remaining ships are most likely to be
+    Hard,
+    // like hard, but also hides its ships where they are least likely to be looked for
+    Brutal,
+}
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Brutal];
+
+    pub fn strategy(self) -> Box<dyn Strategy> {
+        match self {
+            Self::Easy => Box::new(RandomShots::new()),
+            Self::Normal => Box::new(HuntTarget::new()),
+            Self::Hard | Self::Brutal => Box::new(ProbabilityDensity::new(&[])),
+        }
+    }
+    pub fn layout(self) -> Layout {
+        match self {
+            Self::Easy | Self::Normal => Layout::Random,
+            Self::Hard => Layout::SpreadOut,
+            Self::Brutal => Layout::AntiHeatmap,
+        }
+    }
+}
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Brutal => "brutal",
+        })
+    }
+}
+impl FromStr for Difficulty {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string() == s)
+            .ok_or_else(|| format!("unknown difficulty '{s}', use easy, normal, hard or brutal"))
+    }
+}
 
 pub struct Computer {
     board: PlayerBoard,
+    opponent_board: ViewBoard,
+    difficulty: Difficulty,
+    layout: Layout,
+    strategy: Box<dyn Strategy>,
+    rng: GameRng,
     name: String,
 }
 impl Computer {
-    pub fn new() -> Self {
+    pub fn new(difficulty: Difficulty) -> Self {
         Self {
-            board: PlayerBoard::new(),
-            name: String::from("Clanker"),
+            board: PlayerBoard::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
+            opponent_board: ViewBoard::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
+            difficulty,
+            layout: difficulty.layout(),
+            strategy: difficulty.strategy(),
+            rng: rng::from_seed(0),
+            name: format!("Clanker ({difficulty})"),
         }
     }
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+    pub fn restore(state: ComputerState) -> Result<Self, SaveError> {
+        let mut strategy = state.difficulty.strategy();
+        strategy.restore(state.strategy).map_err(SaveError::Parse)?;
+        Ok(Self {
+            board: state.board,
+            opponent_board: state.opponent_board,
+            difficulty: state.difficulty,
+            layout: state.layout,
+            strategy,
+            rng: rng::from_seed(state.seed),
+            name: state.name,
+        })
+    }
+    // now and then a hit is patched up instead of firing
+    fn patch_up(&mut self) -> Option<Action> {
+        let repairable = self.board.repairable();
+        if repairable.is_empty() || !self.rng.random_ratio(1, 4) {
+            return None;
+        }
+        Some(Action::Repair(
+            repairable[self.rng.random_range(0..repairable.len())],
+        ))
+    }
+    // a ship the opponent missed right next to slips away now and then instead of firing
+    fn evade(&mut self) -> Option<Action> {
+        if !self.board.allows_moves() {
+            return None;
+        }
+        let grid = self.board.get_grid();
+        let missed = |p: Point| grid.get(p.y).and_then(|row| row.get(p.x)) == Some(&Tile::Miss);
+        let threatened: Vec<Point> = self
+            .board
+            .fleet()
+            .into_iter()
+            .filter(|ship| ship.iter().any(|p| p.neighbours().any(missed)))
+            .map(|ship| ship[0])
+            .collect();
+        if threatened.is_empty() || !self.rng.random_ratio(1, 3) {
+            return None;
+        }
+        let mut maneuvers = Maneuver::ALL;
+        maneuvers.shuffle(&mut self.rng);
+        threatened.into_iter().find_map(|at| {
+            maneuvers
+                .into_iter()
+                .find(|m| self.board.can_move_ship(at, *m).is_ok())
+                .map(|m| Action::Move(at, m))
+        })
+    }
+}
+// an armored part that held or a repaired hit is a sure hit, it's finished off before the
+// strategy is asked
+pub fn target(strategy: &mut dyn Strategy, view: &ViewBoard, rng: &mut GameRng) -> Point {
+    (0..view.height())
+        .flat_map(|y| (0..view.width()).map(move |x| Point::new(x, y)))
+        .find(|p| matches!(view.tile(*p), Some(Tile::Damaged | Tile::Repaired)))
+        .unwrap_or_else(|| strategy.choose_point(view, rng))
 }
 impl GamePlayer for Computer {
-    fn choose_point(&mut self) -> Point {
-        let x = random_range(0..WIDTH);
-        let y = random_range(0..HEIGHT);
-        Point::new(x, y)
+    fn choose_point(&mut self) -> Action {
+        Action::Shoot(target(
+            self.strategy.as_mut(),
+            &self.opponent_board,
+            &mut self.rng,
+        ))
+    }
+    // the shot the strategy picks grows into a special attack when that reaches enough
+    // unknown cells, the strongest weapon first
+    fn choose_action(&mut self, arsenal: &Arsenal) -> Action {
+        if let Some(action) = self.patch_up().or_else(|| self.evade()) {
+            return action;
+        }
+        let target = target(self.strategy.as_mut(), &self.opponent_board, &mut self.rng);
+        let (width, height) = (self.opponent_board.width(), self.opponent_board.height());
+        let unknown = |weapon: Weapon| {
+            weapon
+                .area(target, width, height)
+                .into_iter()
+                .filter(|p| self.opponent_board.tile(*p).is_some_and(Tile::is_unknown))
+                .count()
+        };
+        let worth = [
+            (Weapon::Airstrike, 3),
+            (Weapon::Torpedo, 3),
+            (Weapon::Radar, 9),
+        ];
+        worth
+            .into_iter()
+            .find(|&(weapon, cells)| arsenal.charges(weapon) > 0 && unknown(weapon) >= cells)
+            .map_or(Action::Shoot(target), |(weapon, _)| {
+                Action::Strike(weapon, target)
+            })
+    }
+    fn choose_salvo(&mut self, count: usize) -> Action {
+        // the strategy sees the targets already picked as misses so it doesn't pick them again
+        let mut view = self.opponent_board.clone();
+        let mut targets = Vec::new();
+        for _ in 0..count.min(view.targets()) {
+            let target = target(self.strategy.as_mut(), &view, &mut self.rng);
+            view.register_shot(ShotResult::Miss, target)
+                .expect("strategies pick cells on the board");
+            targets.push(target);
+        }
+        Action::Salvo(targets)
     }
     fn is_game_over(&self) -> bool {
         self.board.is_game_over()
     }
-    fn process_shot(&mut self, p: Point) -> Result<ShotResult, BoardError> {
+    fn ships_afloat(&self) -> usize {
+        self.board.ships_afloat()
+    }
+    fn process_shot(&mut self, p: Point) -> Result<ShotResult<'_>, BoardError> {
         self.board.process_shot(p)
     }
-    fn update_view_board(&mut self, _: ShotResult, _: Point) -> Result<(), BoardError> {
-        // this computer just chooses random positions each time
-        Ok(())
+    fn update_view_board(&mut self, shot: ShotResult, p: Point) -> Result<(), BoardError> {
+        self.strategy.register_shot(&shot);
+        self.opponent_board.register_shot(shot, p)
+    }
+    fn process_strike(&mut self, weapon: Weapon, p: Point) -> Result<Strike, BoardError> {
+        self.board.process_strike(weapon, p)
+    }
+    fn update_view_strike(
+        &mut self,
+        weapon: Weapon,
+        p: Point,
+        strike: &Strike,
+    ) -> Result<(), BoardError> {
+        if let Strike::Shots { results } = strike {
+            for (_, result) in results {
+                self.strategy.register_shot(&result.as_shot());
+            }
+        }
+        self.opponent_board.register_strike(weapon, p, strike)
+    }
+    fn take_blast(&mut self, p: Point) -> Option<(Point, ShotOutcome)> {
+        self.board.process_blast(p)
+    }
+    fn move_ship(&mut self, at: Point, maneuver: Maneuver) -> Result<usize, BoardError> {
+        self.board.move_ship(at, maneuver)
+    }
+    fn update_view_move(&mut self, _moved: Option<(usize, Maneuver)>) {
+        self.opponent_board.register_move();
+    }
+    fn repair_ship(&mut self, p: Point) -> Result<(), BoardError> {
+        self.board.repair_ship(p)
+    }
+    fn update_view_repair(&mut self, p: Point) -> Result<(), BoardError> {
+        self.opponent_board.register_repair(p)
     }
     fn get_name(&self) -> &String {
         &self.name
     }
     fn render(&self) {}
+    fn fleet(&self) -> Option<Vec<Vec<Point>>> {
+        Some(self.board.fleet())
+    }
+    fn decoys(&self) -> Vec<usize> {
+        self.board.decoys()
+    }
+    fn save(&mut self) -> Option<SavedPlayer> {
+        // the rng can't be written down, so it restarts from a seed both games share
+        let seed = self.rng.random();
+        self.rng = rng::from_seed(seed);
+        Some(SavedPlayer::Computer(ComputerState {
+            board: self.board.clone(),
+            opponent_board: self.opponent_board.clone(),
+            difficulty: self.difficulty,
+            layout: self.layout,
+            strategy: self.strategy.save(),
+            seed,
+            name: self.name.clone(),
+        }))
+    }
+}
+impl Setup<GameConfig> for Computer {
+    fn setup(&mut self, config: GameConfig) {
+        let (width, height) = (config.rules.width, config.rules.height);
+        self.opponent_board = ViewBoard::new(width, height)
+            .with_no_touch(config.rules.no_touch)
+            .with_map(&config.map);
+        self.rng = rng::from_seed(config.seed);
+        self.strategy.setup(&config);
+        let empty = PlayerBoard::new(width, height)
+            .with_no_touch(config.rules.no_touch)
+            .with_moving(config.rules.moving)
+            .with_repairing(config.rules.repair)
+            .with_map(&config.map);
+        // every layout ends with the search the fleet was validated with, so a valid fleet fits
+        self.board = self
+            .layout
+            .place(&empty, &config.fleet, &mut self.rng)
+            .expect("validation makes sure the fleet fits");
+    }
 }
-impl Setup<Vec<ShipBlueprint>> for Computer {
-    fn setup(&mut self, ships: Vec<ShipBlueprint>) {
-        for ship in ships.iter() {
-            // TODO: add selecting of coordinates to put the ship
-            loop {
-                let pos = self.choose_point();
 
-                if self.board.place_ship(ship, pos, Rotation::None).is_ok() {
-                    break;
-                }
-            }
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        Setup,
+        config::GameConfig,
+        player_board::ShotResult,
+        players::{
+            Action, GamePlayer,
+            computer::{Computer, Difficulty, Layout},
+        },
+        point::Point,
+        save::SavedPlayer,
+    };
+
+    // fires n shots that all miss
+    fn shoot(computer: &mut Computer, n: usize) -> Vec<Point> {
+        (0..n)
+            .map(|_| {
+                let Action::Shoot(p) = computer.choose_point() else {
+                    panic!("computers always shoot");
+                };
+                computer
+                    .update_view_board(ShotResult::Miss, p)
+                    .expect("should be in bounds");
+                p
+            })
+            .collect()
+    }
+
+    #[test]
+    fn difficulty_round_trips_through_str() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse(), Ok(difficulty));
+        }
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+    #[test]
+    fn every_layout_can_be_picked() {
+        let config = GameConfig::default();
+        for layout in Layout::ALL {
+            assert_eq!(layout.to_string().parse(), Ok(layout));
+            let mut computer = Computer::new(Difficulty::Normal).with_layout(layout);
+            computer.setup(config.clone());
+            assert_eq!(computer.board.ships.len(), config.fleet.len());
+        }
+    }
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let config = GameConfig::default().with_seed(42);
+        let play = || {
+            let mut computer = Computer::new(Difficulty::Hard);
+            computer.setup(config.clone());
+            let mut layout: Vec<Point> = computer
+                .board
+                .ships
+                .values()
+                .flat_map(|ship| ship.parts.clone())
+                .collect();
+            layout.sort();
+            (layout, shoot(&mut computer, 10))
+        };
+        assert_eq!(play(), play());
+    }
+    #[test]
+    fn restored_computer_plays_on_the_same() {
+        let mut computer = Computer::new(Difficulty::Hard);
+        computer.setup(GameConfig::default().with_seed(42));
+        shoot(&mut computer, 5);
+        let Some(SavedPlayer::Computer(state)) = computer.save() else {
+            panic!("computers can be saved");
+        };
+        let json = serde_json::to_string(&state).expect("should serialize");
+        let state = serde_json::from_str(&json).expect("should deserialize");
+        let mut restored = Computer::restore(state).expect("should restore");
+        assert_eq!(restored.board.fleet(), computer.board.fleet());
+        assert_eq!(shoot(&mut restored, 10), shoot(&mut computer, 10));
+    }
+    #[test]
+    fn salvo_finishes_a_damaged_armored_part() {
+        let mut computer = Computer::new(Difficulty::Easy);
+        computer.setup(GameConfig::default().with_seed(42));
+        let (width, height) = (
+            computer.opponent_board.width(),
+            computer.opponent_board.height(),
+        );
+        for p in (0..height).flat_map(|y| (0..width).map(move |x| Point::new(x, y))) {
+            computer
+                .update_view_board(ShotResult::Miss, p)
+                .expect("should be in bounds");
+        }
+        // the only cell left to shoot at is an armored part that held
+        let armored = Point::new(3, 3);
+        computer
+            .update_view_board(ShotResult::Damaged, armored)
+            .expect("should be in bounds");
+        let Action::Salvo(targets) = computer.choose_salvo(3) else {
+            panic!("computers fire the salvo they are asked for");
+        };
+        assert_eq!(targets, vec![armored]);
+    }
+    #[test]
+    fn every_difficulty_places_the_whole_fleet() {
+        let config = GameConfig::default();
+        for difficulty in Difficulty::ALL {
+            let mut computer = Computer::new(difficulty);
+            computer.setup(config.clone());
+            assert_eq!(computer.board.ships.len(), config.fleet.len());
+            assert!(!computer.is_game_over());
         }
     }
 }
@@ -0,0 +1,168 @@
+use rand::Rng;
+
+use crate::game::{
+    player_board::ViewBoard, players::computer::strategy::Strategy, point::Point, rng::GameRng,
+    tile::Tile,
+};
+
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Mode {
+    Hunt,
+    Target,
+}
+
+// Everything the strategy needs is read from the view board, so a hit that has not been sunk
+// yet keeps it in target mode and sinking the last wounded ship sends it back to hunting.
+pub struct HuntTarget;
+
+impl HuntTarget {
+    pub fn new() -> Self {
+        Self
+    }
+    pub fn mode(&self, view: &ViewBoard) -> Mode {
+        if target_candidates(view).is_empty() {
+            Mode::Hunt
+        } else {
+            Mode::Target
+        }
+    }
+}
+impl Strategy for HuntTarget {
+    fn choose_point(&mut self, view: &ViewBoard, rng: &mut GameRng) -> Point {
+        let pool = match self.mode(view) {
+            Mode::Target => target_candidates(view),
+            Mode::Hunt => hunt_candidates(view),
+        };
+        if pool.is_empty() {
+            // nothing left to shoot at, the game is over anyway
+            return Point::new(0, 0);
+        }
+        pool[rng.random_range(0..pool.len())]
+    }
+}
+
+// ships are at least two cells long most of the time, so a checkerboard finds them in half the
+// shots, single cell ships are picked up once the checkerboard is exhausted
+fn hunt_candidates(view: &ViewBoard) -> Vec<Point> {
+    let hidden = hidden_points(view);
+    let parity: Vec<Point> = hidden
+        .iter()
+        .copied()
+        .filter(|p| (p.x + p.y) % 2 == 0)
+        .collect();
+    if parity.is_empty() { hidden } else { parity }
+}
+
+fn hidden_points(view: &ViewBoard) -> Vec<Point> {
+    (0..view.height())
+        .flat_map(|y| (0..view.width()).map(move |x| Point::new(x, y)))
+        .filter(|p| view.tile(*p).is_some_and(Tile::is_unknown))
+        .collect()
+}
+
+// hidden cells next to wounded ships, cells that extend a line of hits come first
+fn target_candidates(view: &ViewBoard) -> Vec<Point> {
+    let mut in_line = Vec::new();
+    let mut around = Vec::new();
+    for hit in (0..view.height())
+        .flat_map(|y| (0..view.width()).map(move |x| Point::new(x, y)))
+        .filter(|p| view.tile(*p) == Some(Tile::Hit))
+    {
+        for (dx, dy) in DIRECTIONS {
+            let Some(next) = hit.offset(dx, dy) else {
+                continue;
+            };
+            if !view.tile(next).is_some_and(Tile::is_unknown) || around.contains(&next) {
+                continue;
+            }
+            around.push(next);
+            let behind = hit.offset(-dx, -dy).and_then(|p| view.tile(p));
+            if behind == Some(Tile::Hit) {
+                in_line.push(next);
+            }
+        }
+    }
+    if in_line.is_empty() { around } else { in_line }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::game::{
+        player_board::{PlayerBoard, ShotResult, ViewBoard},
+        players::computer::{
+            hunt_target::{HuntTarget, Mode},
+            strategy::Strategy,
+        },
+        point::Point,
+        rng,
+        rotation::Rotation,
+        ship::ShipBlueprint,
+    };
+
+    fn line(len: usize) -> ShipBlueprint {
+        ShipBlueprint::new(
+            (0..len).map(|x| Point::new(x, 0)).collect(),
+            String::from("line"),
+        )
+    }
+
+    #[test]
+    fn never_repeats_a_shot() {
+        let mut rng = rng::from_seed(7);
+        let mut board = PlayerBoard::new(8, 8);
+        board
+            .place_ship(&line(3), Point::new(2, 2), Rotation::None)
+            .expect("should be ok to place");
+        let mut view = ViewBoard::new(8, 8);
+        let mut ai = HuntTarget::new();
+        let mut shot = HashSet::new();
+        while !board.is_game_over() {
+            let p = ai.choose_point(&view, &mut rng);
+            assert!(shot.insert((p.x, p.y)), "shot {p:?} twice");
+            let res = board.process_shot(p).expect("should be a new shot");
+            view.register_shot(res, p).expect("should be in bounds");
+        }
+    }
+    #[test]
+    fn targets_neighbours_after_hit() {
+        let mut rng = rng::from_seed(7);
+        let mut view = ViewBoard::new(8, 8);
+        let mut ai = HuntTarget::new();
+        assert_eq!(ai.mode(&view), Mode::Hunt);
+        let hit = Point::new(4, 4);
+        view.register_shot(ShotResult::Hit, hit)
+            .expect("should be in bounds");
+        assert_eq!(ai.mode(&view), Mode::Target);
+        let p = ai.choose_point(&view, &mut rng);
+        assert_eq!(p.x.abs_diff(hit.x) + p.y.abs_diff(hit.y), 1);
+    }
+    #[test]
+    fn follows_line_of_hits() {
+        let mut rng = rng::from_seed(7);
+        let mut view = ViewBoard::new(8, 8);
+        let mut ai = HuntTarget::new();
+        view.register_shot(ShotResult::Hit, Point::new(3, 4))
+            .expect("should be in bounds");
+        view.register_shot(ShotResult::Hit, Point::new(4, 4))
+            .expect("should be in bounds");
+        for _ in 0..20 {
+            let p = ai.choose_point(&view, &mut rng);
+            assert!(p == Point::new(2, 4) || p == Point::new(5, 4), "{p:?}");
+        }
+    }
+    #[test]
+    fn hunts_again_after_sinking() {
+        let mut view = ViewBoard::new(8, 8);
+        let ai = HuntTarget::new();
+        let parts = vec![Point::new(4, 4), Point::new(5, 4)];
+        view.register_shot(ShotResult::Hit, parts[0])
+            .expect("should be in bounds");
+        view.register_shot(ShotResult::ShipSunk(&parts), parts[1])
+            .expect("should be in bounds");
+        assert_eq!(ai.mode(&view), Mode::Hunt);
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    player_board::{BoardError, Fit, PlacementError, PlayerBoard, ViewBoard, biggest_first},
+    players::computer::probability::ProbabilityDensity,
+    point::Point,
+    rng::GameRng,
+    rotation::Rotation,
+    ship::ShipBlueprint,
+};
+
+// how many times the whole fleet is placed from scratch before giving up
+const MAX_ATTEMPTS: usize = 50;
+
+// How the computer arranges its fleet. Every layout picks rotations at random too, the
+// non random ones only prefer some positions over others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    #[default]
+    Random,
+    // keeps the ships as far from each other as possible
+    SpreadOut,
+    // puts as many ship cells on the border as possible
+    EdgeHugging,
+    // avoids the cells a density based opponent shoots first
+    AntiHeatmap,
+}
+
+struct Placement {
+    pos: Point,
+    rotation: Rotation,
+    cells: Vec<Point>,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 4] = [
+        Self::Random,
+        Self::SpreadOut,
+        Self::EdgeHugging,
+        Self::AntiHeatmap,
+    ];
+
+    // fills a copy of the empty board, which carries the size and the placement rules
+    pub fn place(
+        self,
+        empty: &PlayerBoard,
+        fleet: &[ShipBlueprint],
+        rng: &mut GameRng,
+    ) -> Result<PlayerBoard, BoardError> {
+        let (width, height) = (empty.width(), empty.height());
+        let order = biggest_first(fleet);
+        let ships: Vec<&ShipBlueprint> = order.iter().map(|&i| &fleet[i]).collect();
+
+        let heat = match self {
+            Self::AntiHeatmap => {
+                ProbabilityDensity::new(fleet).density(&ViewBoard::new(width, height))
+            }
+            _ => Vec::new(),
+        };
+        for _ in 0..MAX_ATTEMPTS {
+            if let Some(mut board) = self.attempt(empty, &ships, &heat, rng)? {
+                board.renumber(&order);
+                return Ok(board);
+            }
+        }
+        // the fleet may only fit a few ways that random picks keep missing
+        match empty.fit_fleet(fleet) {
+            Fit::Placed(board) => Ok(board),
+            // validation refuses a fleet the search gives up on, as nothing else would place it
+            Fit::Impossible | Fit::GaveUp => Err(BoardError::ShipPlacementError(
+                PlacementError::FleetDoesNotFit,
+            )),
+        }
+    }
+    // places the ships one after the other, None when one of them has no room left
+    fn attempt(
+        self,
+        empty: &PlayerBoard,
+        ships: &[&ShipBlueprint],
+        heat: &[Vec<usize>],
+        rng: &mut GameRng,
+    ) -> Result<Option<PlayerBoard>, BoardError> {
+        let mut board = empty.clone();
+        for ship in ships {
+            let placements = legal_placements(&board, ship);
+            if placements.is_empty() {
+                // painted ourselves into a corner, start over
+                return Ok(None);
+            }
+            let placement = self.pick(&board, heat, placements, rng);
+            board.place_ship(ship, placement.pos, placement.rotation)?;
+        }
+        Ok(Some(board))
+    }
+
+    // scores every placement (lower is better) and picks one of the best few at random
+    fn pick(
+        self,
+        board: &PlayerBoard,
+        heat: &[Vec<usize>],
+        placements: Vec<Placement>,
+        rng: &mut GameRng,
+    ) -> Placement {
+        let mut scored: Vec<(usize, Placement)> = placements
+            .into_iter()
+            .map(|placement| (self.score(board, heat, &placement.cells), placement))
+            .collect();
+        scored.sort_by_key(|(score, _)| *score);
+        let pool = match self {
+            Self::Random => scored.len(),
+            _ => scored.len().div_ceil(10),
+        };
+        scored.swap_remove(rng.random_range(0..pool)).1
+    }
+
+    fn score(self, board: &PlayerBoard, heat: &[Vec<usize>], cells: &[Point]) -> usize {
+        match self {
+            Self::Random => 0,
+            Self::SpreadOut => {
+                let closest = board
+                    .ships
+                    .values()
+                    .flat_map(|ship| ship.parts.iter())
+                    .flat_map(|part| {
+                        cells
+                            .iter()
+                            .map(move |cell| cell.x.abs_diff(part.x) + cell.y.abs_diff(part.y))
+                    })
+                    .min()
+                    .unwrap_or(usize::MAX);
+                usize::MAX - closest
+            }
+            Self::EdgeHugging => cells
+                .iter()
+                .filter(|cell| {
+                    cell.x != 0
+                        && cell.y != 0
+                        && cell.x != board.width() - 1
+                        && cell.y != board.height() - 1
+                })
+                .count(),
+            Self::AntiHeatmap => cells.iter().map(|cell| heat[cell.y][cell.x]).sum(),
+        }
+    }
+}
+impl Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Random => "random",
+            Self::SpreadOut => "spread-out",
+            Self::EdgeHugging => "edge-hugging",
+            Self::AntiHeatmap => "anti-heatmap",
+        })
+    }
+}
+impl FromStr for Layout {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|layout| layout.to_string() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown layout '{s}', use random, spread-out, edge-hugging or anti-heatmap"
+                )
+            })
+    }
+}
+
+fn legal_placements(board: &PlayerBoard, ship: &ShipBlueprint) -> Vec<Placement> {
+    let mut placements = Vec::new();
+    let mut rotation = Rotation::None;
+    loop {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let pos = Point::new(x, y);
+                if let Ok(parts) = board.can_place_ship(ship, pos, rotation) {
+                    placements.push(Placement {
+                        pos,
+                        rotation,
+                        cells: parts.iter().map(|p| Point::new(p.x + x, p.y + y)).collect(),
+                    });
+                }
+            }
+        }
+        rotation = rotation.next();
+        if rotation == Rotation::None {
+            break placements;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        player_board::{BoardError, Fit, PlacementError, PlayerBoard},
+        players::computer::layout::Layout,
+        point::Point,
+        rng,
+        ship::ShipBlueprint,
+    };
+
+    fn line(len: usize) -> ShipBlueprint {
+        ShipBlueprint::new(
+            (0..len).map(|x| Point::new(x, 0)).collect(),
+            String::from("line"),
+        )
+    }
+
+    #[test]
+    fn every_layout_places_the_fleet() {
+        let mut rng = rng::from_seed(7);
+        let fleet = [line(5), line(4), line(3), line(3), line(2)];
+        for layout in Layout::ALL {
+            let board = layout
+                .place(&PlayerBoard::new(10, 10), &fleet, &mut rng)
+                .expect("fleet should fit");
+            assert_eq!(board.ships.len(), fleet.len());
+        }
+    }
+    #[test]
+    fn ships_keep_their_place_in_the_fleet() {
+        let mut rng = rng::from_seed(7);
+        // not listed biggest first, the way the ships are placed
+        let fleet = [line(2), line(4), line(3)];
+        for layout in Layout::ALL {
+            let board = layout
+                .place(&PlayerBoard::new(10, 10), &fleet, &mut rng)
+                .expect("fleet should fit");
+            let sizes: Vec<usize> = board.fleet().iter().map(Vec::len).collect();
+            assert_eq!(sizes, vec![2, 4, 3]);
+        }
+        let Fit::Placed(board) = PlayerBoard::new(10, 10).fit_fleet(&fleet) else {
+            panic!("fleet should fit");
+        };
+        let sizes: Vec<usize> = board.fleet().iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 4, 3]);
+        assert!(board.ships.iter().all(|(id, ship)| ship.id == *id));
+    }
+    #[test]
+    fn ships_get_rotated() {
+        let mut rng = rng::from_seed(7);
+        let rotated = (0..50)
+            .filter_map(|_| {
+                Layout::Random
+                    .place(&PlayerBoard::new(10, 10), &[line(4)], &mut rng)
+                    .ok()
+            })
+            .any(|board| {
+                let ship = board.ships.values().next().expect("ship was placed");
+                ship.parts.iter().all(|p| p.x == ship.parts[0].x)
+            });
+        assert!(rotated);
+    }
+    #[test]
+    fn fleet_that_cannot_fit_is_an_error() {
+        let mut rng = rng::from_seed(7);
+        // three 3x1 ships fit in the 9 cells only side by side, four never do
+        let res = Layout::Random.place(
+            &PlayerBoard::new(3, 3),
+            &[line(3), line(3), line(3), line(3)],
+            &mut rng,
+        );
+        assert_eq!(
+            res.err(),
+            Some(BoardError::ShipPlacementError(
+                PlacementError::FleetDoesNotFit
+            ))
+        );
+        assert!(
+            Layout::Random
+                .place(
+                    &PlayerBoard::new(3, 3),
+                    &[line(3), line(3), line(3)],
+                    &mut rng
+                )
+                .is_ok()
+        );
+    }
+    #[test]
+    fn fleet_that_only_just_fits_is_placed() {
+        let mut rng = rng::from_seed(7);
+        // twelve lines of three cover the board with nothing to spare, random picks never manage
+        let fleet = vec![line(3); 12];
+        for layout in Layout::ALL {
+            let board = layout
+                .place(&PlayerBoard::new(6, 6), &fleet, &mut rng)
+                .expect("fleet should fit");
+            assert_eq!(board.ships.len(), 12);
+        }
+    }
+    #[test]
+    fn edge_hugging_stays_on_the_border() {
+        let mut rng = rng::from_seed(7);
+        let board = Layout::EdgeHugging
+            .place(&PlayerBoard::new(10, 10), &[line(3)], &mut rng)
+            .expect("fleet should fit");
+        let ship = board.ships.values().next().expect("ship was placed");
+        assert!(
+            ship.parts
+                .iter()
+                .all(|p| p.x == 0 || p.y == 0 || p.x == 9 || p.y == 9)
+        );
+    }
+    #[test]
+    fn no_touch_layouts_leave_a_gap() {
+        let mut rng = rng::from_seed(7);
+        let fleet = [line(5), line(4), line(3), line(3), line(2)];
+        for layout in Layout::ALL {
+            let board = layout
+                .place(
+                    &PlayerBoard::new(10, 10).with_no_touch(true),
+                    &fleet,
+                    &mut rng,
+                )
+                .expect("fleet should fit");
+            for (id, ship) in &board.ships {
+                let touching =
+                    board
+                        .ships
+                        .iter()
+                        .filter(|(other, _)| *other != id)
+                        .any(|(_, other)| {
+                            ship.parts
+                                .iter()
+                                .flat_map(|p| p.neighbours())
+                                .any(|p| other.parts.contains(&p))
+                        });
+                assert!(!touching);
+            }
+        }
+    }
+}
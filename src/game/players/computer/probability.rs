@@ -0,0 +1,216 @@
+use rand::Rng;
+
+use crate::game::{
+    config::GameConfig,
+    player_board::{ShotResult, ViewBoard},
+    players::computer::{hunt_target::HuntTarget, strategy::Strategy},
+    point::Point,
+    rng::GameRng,
+    ship::{ShipBlueprint, normalize},
+    tile::Tile,
+};
+
+// how much more a placement counts for every unsunk hit it explains
+const HIT_WEIGHT: usize = 20;
+
+// Enumerates every placement of every ship still afloat that agrees with what is known about
+// the opponent's board and shoots the hidden cell that the most placements go through.
+pub struct ProbabilityDensity {
+    afloat: Vec<ShipBlueprint>,
+    // used when the known tiles can't be explained by the remaining ships
+    fallback: HuntTarget,
+}
+
+impl ProbabilityDensity {
+    pub fn new(fleet: &[ShipBlueprint]) -> Self {
+        Self {
+            afloat: fleet.to_vec(),
+            fallback: HuntTarget::new(),
+        }
+    }
+    // placement count for every cell, cells that were already shot are always 0
+    pub fn density(&self, view: &ViewBoard) -> Vec<Vec<usize>> {
+        let (width, height) = (view.width(), view.height());
+        let mut density = vec![vec![0; width]; height];
+        for ship in &self.afloat {
+            for shape in ship.rotations() {
+                let shape_width = shape.iter().map(|p| p.x).max().unwrap_or(0) + 1;
+                let shape_height = shape.iter().map(|p| p.y).max().unwrap_or(0) + 1;
+                if shape_width > width || shape_height > height {
+                    continue;
+                }
+                for y in 0..=height - shape_height {
+                    for x in 0..=width - shape_width {
+                        let cells: Vec<Point> =
+                            shape.iter().map(|p| Point::new(p.x + x, p.y + y)).collect();
+                        let Some(weight) = placement_weight(view, &cells) else {
+                            continue;
+                        };
+                        for cell in cells {
+                            if view.tile(cell).is_some_and(Tile::is_unknown) {
+                                density[cell.y][cell.x] += weight;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        density
+    }
+}
+impl Strategy for ProbabilityDensity {
+    fn setup(&mut self, config: &GameConfig) {
+        self.afloat = config.fleet.clone();
+    }
+    fn choose_point(&mut self, view: &ViewBoard, rng: &mut GameRng) -> Point {
+        let density = self.density(view);
+        let best = density.iter().flatten().copied().max().unwrap_or(0);
+        if best == 0 {
+            return self.fallback.choose_point(view, rng);
+        }
+        let candidates: Vec<Point> = density
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|&(_, count)| *count == best)
+                    .map(move |(x, _)| Point::new(x, y))
+            })
+            .collect();
+        candidates[rng.random_range(0..candidates.len())]
+    }
+    fn register_shot(&mut self, shot: &ShotResult) {
+        let (parts, decoy) = match shot {
+            ShotResult::ShipSunk(parts) => (parts, false),
+            ShotResult::Decoy(parts) => (parts, true),
+            _ => return,
+        };
+        let sunk = normalize(parts);
+        // a blueprint of the right kind is taken first
+        let index = self
+            .afloat
+            .iter()
+            .position(|ship| ship.decoy == decoy && ship.rotations().contains(&sunk))
+            .or_else(|| {
+                self.afloat
+                    .iter()
+                    .position(|ship| ship.rotations().contains(&sunk))
+            })
+            .or_else(|| {
+                self.afloat
+                    .iter()
+                    .position(|ship| ship.parts.len() == sunk.len())
+            });
+        if let Some(index) = index {
+            self.afloat.remove(index);
+        }
+    }
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(&self.afloat).expect("blueprints always serialize")
+    }
+    fn restore(&mut self, state: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.afloat = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
+
+// None when the ship can't be there, otherwise how likely the placement is
+fn placement_weight(view: &ViewBoard, cells: &[Point]) -> Option<usize> {
+    let mut hits = 0;
+    for cell in cells {
+        match view.tile(*cell)? {
+            Tile::Hidden | Tile::Scanned | Tile::Pinged(_) | Tile::Stale => {}
+            Tile::Hit | Tile::Damaged | Tile::Repaired => hits += 1,
+            _ => return None,
+        }
+    }
+    Some(1 + hits * HIT_WEIGHT)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::game::{
+        player_board::{PlayerBoard, ShotResult, ViewBoard},
+        players::computer::{probability::ProbabilityDensity, strategy::Strategy},
+        point::Point,
+        rng,
+        rotation::Rotation,
+        ship::ShipBlueprint,
+    };
+
+    fn line(len: usize) -> ShipBlueprint {
+        ShipBlueprint::new(
+            (0..len).map(|x| Point::new(x, 0)).collect(),
+            String::from("line"),
+        )
+    }
+    fn corner() -> ShipBlueprint {
+        ShipBlueprint::new(
+            vec![Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)],
+            String::from("corner"),
+        )
+    }
+
+    #[test]
+    fn center_is_denser_than_corner() {
+        let ai = ProbabilityDensity::new(&[line(3)]);
+        let density = ai.density(&ViewBoard::new(7, 7));
+        assert!(density[3][3] > density[0][0]);
+        assert_eq!(density[3][3], 6);
+    }
+    #[test]
+    fn misses_rule_out_placements() {
+        let ai = ProbabilityDensity::new(&[line(3)]);
+        let mut view = ViewBoard::new(5, 1);
+        view.register_shot(ShotResult::Miss, Point::new(2, 0))
+            .expect("should be in bounds");
+        assert_eq!(ai.density(&view), vec![vec![0, 0, 0, 0, 0]]);
+    }
+    #[test]
+    fn shoots_next_to_hits() {
+        let mut rng = rng::from_seed(7);
+        let mut ai = ProbabilityDensity::new(&[line(2)]);
+        let mut view = ViewBoard::new(6, 6);
+        view.register_shot(ShotResult::Hit, Point::new(2, 2))
+            .expect("should be in bounds");
+        let p = ai.choose_point(&view, &mut rng);
+        assert_eq!(p.x.abs_diff(2) + p.y.abs_diff(2), 1);
+    }
+    #[test]
+    fn sunk_ships_are_no_longer_counted() {
+        let mut ai = ProbabilityDensity::new(&[line(2), corner()]);
+        let parts = vec![Point::new(3, 1), Point::new(3, 2), Point::new(4, 2)];
+        ai.register_shot(&ShotResult::ShipSunk(&parts));
+        assert_eq!(ai.afloat.len(), 1);
+        assert_eq!(ai.afloat[0].name, "line");
+    }
+    #[test]
+    fn finishes_a_game_without_repeating() {
+        let mut rng = rng::from_seed(7);
+        let fleet = [line(4), corner(), corner()];
+        let mut board = PlayerBoard::new(8, 8);
+        board
+            .place_ship(&fleet[0], Point::new(0, 7), Rotation::None)
+            .expect("should be ok to place");
+        board
+            .place_ship(&fleet[1], Point::new(5, 1), Rotation::Horizontal)
+            .expect("should be ok to place");
+        board
+            .place_ship(&fleet[2], Point::new(2, 3), Rotation::Vertical)
+            .expect("should be ok to place");
+        let mut view = ViewBoard::new(8, 8);
+        let mut ai = ProbabilityDensity::new(&fleet);
+        let mut shot = HashSet::new();
+        while !board.is_game_over() {
+            let p = ai.choose_point(&view, &mut rng);
+            assert!(shot.insert(p), "shot {p:?} twice");
+            let res = board.process_shot(p).expect("should be a new shot");
+            ai.register_shot(&res);
+            view.register_shot(res, p).expect("should be in bounds");
+        }
+        assert!(ai.afloat.is_empty());
+    }
+}
@@ -0,0 +1,27 @@
+use rand::Rng;
+
+use crate::game::{
+    player_board::ViewBoard, players::computer::strategy::Strategy, point::Point, rng::GameRng,
+    tile::Tile,
+};
+
+// Shoots any cell it hasn't shot yet, it doesn't care what it hit before.
+pub struct RandomShots;
+
+impl RandomShots {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl Strategy for RandomShots {
+    fn choose_point(&mut self, view: &ViewBoard, rng: &mut GameRng) -> Point {
+        let hidden: Vec<Point> = (0..view.height())
+            .flat_map(|y| (0..view.width()).map(move |x| Point::new(x, y)))
+            .filter(|p| view.tile(*p).is_some_and(Tile::is_unknown))
+            .collect();
+        if hidden.is_empty() {
+            return Point::new(0, 0);
+        }
+        hidden[rng.random_range(0..hidden.len())]
+    }
+}
@@ -0,0 +1,23 @@
+use crate::game::{
+    config::GameConfig,
+    player_board::{ShotResult, ViewBoard},
+    point::Point,
+    rng::GameRng,
+};
+
+// How a computer picks its shots. New difficulty levels only need to implement this and
+// be returned from Difficulty::strategy.
+pub trait Strategy {
+    // called at the start of every game, before any shot is taken
+    fn setup(&mut self, _config: &GameConfig) {}
+    fn choose_point(&mut self, view: &ViewBoard, rng: &mut GameRng) -> Point;
+    // called with the result of every shot, before it is written into the view board
+    fn register_shot(&mut self, _shot: &ShotResult) {}
+    // whatever the strategy remembers that can't be read off the view board, for save files
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+    fn restore(&mut self, _state: serde_json::Value) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+}
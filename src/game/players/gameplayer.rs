@@ -1,17 +1,116 @@
 use crate::game::{
     Setup,
-    player_board::{BoardError, ShotResult, board_view::BoardView},
+    config::GameConfig,
+    net::commitment::TamperError,
+    player_board::ShotOutcome,
+    player_board::{BoardError, ShotResult},
     point::Point,
-    ship::ShipBlueprint,
+    save::SavedPlayer,
+    ship::Maneuver,
+    weapon::{Arsenal, Strike, Weapon},
 };
 
-pub trait GamePlayer: Setup<Vec<ShipBlueprint>> {
+// what a player does with their turn
+pub enum Action {
+    Shoot(Point),
+    // every target of a salvo, all picked before any of them is fired at
+    Salvo(Vec<Point>),
+    // a special attack aimed at the point
+    Strike(Weapon, Point),
+    // instead of firing, the undamaged ship with a part at the point moves
+    Move(Point, Maneuver),
+    // a player whose board lives elsewhere moved one of their ships, which one stays hidden
+    Moved,
+    // instead of firing, the destroyed ship part at the point is repaired
+    Repair(Point),
+    // stop playing, the game is saved if every player can be
+    SaveAndQuit,
+    // the player left for good and gives the game up, see GamePlayer::gone
+    Forfeit,
+}
+
+pub trait GamePlayer: Setup<GameConfig> {
     // TODO: Should i have just put the terminal to each method that requires it instead of putting
     // it inside the Player struct?
-    fn choose_point(&mut self) -> Point;
+    fn choose_point(&mut self) -> Action;
+    // picks up to count targets at once, fewer when there aren't that many cells left
+    fn choose_salvo(&mut self, count: usize) -> Action;
+    // like choose_point, a special attack may be picked while the arsenal has charges left
+    fn choose_action(&mut self, _arsenal: &Arsenal) -> Action {
+        self.choose_point()
+    }
     fn is_game_over(&self) -> bool;
-    fn process_shot(&mut self, p: Point) -> Result<ShotResult, BoardError>;
+    fn ships_afloat(&self) -> usize;
+    fn process_shot(&mut self, p: Point) -> Result<ShotResult<'_>, BoardError>;
     fn update_view_board(&mut self, shot: ShotResult, p: Point) -> Result<(), BoardError>;
+    fn process_strike(&mut self, weapon: Weapon, p: Point) -> Result<Strike, BoardError>;
+    fn update_view_strike(
+        &mut self,
+        weapon: Weapon,
+        p: Point,
+        strike: &Strike,
+    ) -> Result<(), BoardError>;
+    // resolves every shot of a salvo before any result is handed out
+    fn process_salvo(&mut self, points: &[Point]) -> Vec<(Point, Result<ShotOutcome, BoardError>)> {
+        points
+            .iter()
+            .map(|&p| (p, self.process_shot(p).map(|shot| ShotOutcome::from(&shot))))
+            .collect()
+    }
+    fn update_view_salvo(&mut self, results: &[(Point, ShotOutcome)]) -> Result<(), BoardError> {
+        for (p, result) in results {
+            self.update_view_board(result.as_shot(), *p)?;
+        }
+        Ok(())
+    }
+    // this player's shot set off a mine at p, their own fleet takes the blast, returns the part it
+    // hit and what it did to it, None when no ship is left to hit
+    fn take_blast(&mut self, p: Point) -> Option<(Point, ShotOutcome)>;
+    // a mine of ours went off under the opponent, the blast hit their fleet at p
+    fn update_view_blast(&mut self, p: Point, result: &ShotOutcome) -> Result<(), BoardError> {
+        self.update_view_board(result.as_shot(), p)
+    }
+    // moves one of this player's own ships, returns its place in the fleet
+    fn move_ship(&mut self, at: Point, maneuver: Maneuver) -> Result<usize, BoardError>;
+    // the opponent moved a ship instead of firing, which one and how when that is known here
+    fn update_view_move(&mut self, moved: Option<(usize, Maneuver)>);
+    // repairs the destroyed part of one of this player's own ships at p
+    fn repair_ship(&mut self, p: Point) -> Result<(), BoardError>;
+    // the opponent repaired the hit at p instead of firing
+    fn update_view_repair(&mut self, p: Point) -> Result<(), BoardError>;
     fn get_name(&self) -> &String;
     fn render(&self);
+    // the ships of a player whose board lives in this process, ordered by ship id
+    fn fleet(&self) -> Option<Vec<Vec<Point>>> {
+        None
+    }
+    // the positions in fleet() of the ships that are only decoys
+    fn decoys(&self) -> Vec<usize> {
+        Vec::new()
+    }
+    // the ships a player whose board lives elsewhere moved and how, once they were revealed
+    fn moves(&self) -> Option<Vec<(usize, Maneuver)>> {
+        None
+    }
+    // called before setup with the seed the whole game is played from, a player at the screen
+    // shows it so it can go into bug reports
+    fn show_seed(&mut self, _seed: u64) {}
+    // called once both fleets are placed, with the other player's fleet and decoys if it lives here
+    fn commit(&mut self, _opponent_fleet: Option<Vec<Vec<Point>>>, _opponent_decoys: Vec<usize>) {}
+    // why the player left the game for good, a remote player whose connection was lost
+    fn gone(&self) -> Option<String> {
+        None
+    }
+    // the lie a player who is gone was caught at before the game was over
+    fn caught(&mut self) -> Option<TamperError> {
+        None
+    }
+    // called once the game is over, checks that every shot at this player was answered truthfully
+    fn verify(&mut self) -> Result<(), TamperError> {
+        Ok(())
+    }
+    // everything needed to pick the game up again later, None if the player can't be saved
+    fn save(&mut self) -> Option<SavedPlayer> {
+        None
+    }
 }
@@ -1,6 +1,8 @@
 mod computer;
 mod gameplayer;
 mod player;
-pub use computer::Computer;
-pub use gameplayer::GamePlayer;
-pub use player::Player;
+mod remote;
+pub use computer::{Computer, Difficulty, Layout, Strategy, target};
+pub use gameplayer::{Action, GamePlayer};
+pub use player::{Player, place_fleet};
+pub use remote::RemotePlayer;
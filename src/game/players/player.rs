@@ -1,16 +1,25 @@
-use crossterm::event::Event;
+use crossterm::event::{Event, KeyCode};
 use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::text::Text;
 
+use crate::game::config::GameConfig;
 use crate::game::cursor::Cursor;
+use crate::game::player_board::ShotOutcome;
 use crate::game::player_board::board_builder::BoardBuilder;
 use crate::game::player_board::board_view::BoardView;
-use crate::game::player_board::{BoardError, HEIGHT, ViewBoard, WIDTH, board_view};
-use crate::game::players::GamePlayer;
+use crate::game::player_board::{
+    BoardError, DEFAULT_HEIGHT, DEFAULT_WIDTH, PlacementError, ShotError, ViewBoard,
+};
+use crate::game::players::{Action, GamePlayer};
+use crate::game::save::{HumanState, SavedPlayer};
+use crate::game::ship::{Maneuver, ShipBlueprint};
+use crate::game::tile::Tile;
+use crate::game::ui::WaitForKey;
+use crate::game::weapon::{Arsenal, Strike, Weapon};
 use crate::game::{
     Setup,
     player_board::{PlayerBoard, ShotResult},
     point::Point,
-    ship::ShipBlueprint,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -20,28 +29,61 @@ pub struct Player {
     terminal: Rc<RefCell<ratatui::DefaultTerminal>>,
     last_cursor_pos: Option<Point>,
     name: String,
+    // the seed of the game, shown while the fleet is placed
+    seed: Option<u64>,
 }
 impl Player {
     pub fn new(terminal: Rc<RefCell<ratatui::DefaultTerminal>>, name: String) -> Self {
         Self {
-            board: PlayerBoard::new(),
-            opponent_board: ViewBoard::new(),
+            board: PlayerBoard::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
+            opponent_board: ViewBoard::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
             terminal,
             last_cursor_pos: None,
             name,
+            seed: None,
+        }
+    }
+    pub fn restore(terminal: Rc<RefCell<ratatui::DefaultTerminal>>, state: HumanState) -> Self {
+        Self {
+            board: state.board,
+            opponent_board: state.opponent_board,
+            terminal,
+            last_cursor_pos: state.last_cursor_pos,
+            name: state.name,
+            seed: None,
         }
     }
+    fn confirm_quit(&self) -> bool {
+        let wait = WaitForKey::new(Text::from("Save the game and quit? (y/n)"));
+        let _ = self
+            .terminal
+            .borrow_mut()
+            .draw(|f| wait.render(f, f.area()));
+        wait.wait(Some(vec!['y', 'n'])) == 'y'
+    }
     // opponent_board is passed in so that you can select points in choose_point
     pub fn render_view(&self, opponent_board: &BoardView) {
-        // TWO BOARD VIEWS FIRST OPPONENT, SECOND SELF
         let self_board = BoardView::new(self.board.get_grid(), None, "Your ships");
-        self.terminal.borrow_mut().draw(|f| {
+        self.render_boards(opponent_board, &self_board);
+    }
+    fn render_boards(&self, opponent_board: &BoardView, self_board: &BoardView) {
+        // TWO BOARD VIEWS FIRST OPPONENT, SECOND SELF
+        let board_width = self.board.width() as u16 + 2; // Width of a board + borders
+        let board_height = self.board.height() as u16 + 2; // Height of a board + borders
+        let _ = self.terminal.borrow_mut().draw(|f| {
+            // Stack the boards when they fit above each other, otherwise put them side by side
+            let stacked = f.area().height > board_height * 2;
+            let (direction, total_width, total_height) = if stacked {
+                (Direction::Vertical, board_width, board_height * 2 + 1)
+            } else {
+                (Direction::Horizontal, board_width * 2 + 2, board_height)
+            };
             // Create a centered layout for both boards
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
                     Constraint::Min(0),
-                    Constraint::Length((HEIGHT as u16 + 2) * 2 + 3), // Total height of both boards + spacing
+                    Constraint::Length(total_height),
                     Constraint::Min(0),
                 ])
                 .split(f.area());
@@ -50,18 +92,20 @@ impl Player {
                 .direction(Direction::Horizontal)
                 .constraints([
                     Constraint::Min(0),
-                    Constraint::Length(WIDTH as u16 + 2), // Width of boards + borders
+                    Constraint::Length(total_width),
                     Constraint::Min(0),
                 ])
                 .split(chunks[1]);
 
             // Split the centered area for the two boards
+            let board_size = if stacked { board_height } else { board_width };
+            let spacer = if stacked { 1 } else { 2 };
             let board_chunks = Layout::default()
-                .direction(Direction::Vertical)
+                .direction(direction)
                 .constraints([
-                    Constraint::Length(HEIGHT as u16 + 2), // Opponent board height
-                    Constraint::Length(1),                 // Spacer
-                    Constraint::Length(HEIGHT as u16 + 2), // Self board height
+                    Constraint::Length(board_size), // Opponent board
+                    Constraint::Length(spacer),     // Spacer
+                    Constraint::Length(board_size), // Self board
                 ])
                 .split(inner_chunks[1]);
 
@@ -69,24 +113,145 @@ impl Player {
             self_board.render(f, board_chunks[2]);
         });
     }
+    // a cell of our own board picked with the cursor, None when esc was pressed
+    fn pick_own_cell(&self, pos: &mut Point, title: &str) -> Option<Point> {
+        let (width, height) = (self.board.width(), self.board.height());
+        loop {
+            let cursor = Cursor::new(pos.x, pos.y, width, height);
+            let mut self_board = BoardView::new(self.board.get_grid(), Some(cursor), title);
+            let opponent_board = BoardView::new(self.opponent_board.get_grid(), None, "");
+            self.render_boards(&opponent_board, &self_board);
+            let event = crossterm::event::read();
+            let Ok(Event::Key(e)) = event else { continue };
+            let res = self_board.handle_key(e);
+            *pos = self_board.cursor_pos().unwrap_or(*pos);
+            match res {
+                Err(BoardError::Shot(ShotError::ShotExit)) => return None,
+                Ok(Some(p)) => return Some(p),
+                _ => continue,
+            }
+        }
+    }
+    // picks one of our ships and where it goes, None to go back to firing
+    fn choose_move(&self) -> Option<Action> {
+        let mut pos = Point::new(0, 0);
+        loop {
+            let at = self.pick_own_cell(&mut pos, "pick a ship to move, esc to fire")?;
+            let fleet = self.board.fleet();
+            let Some(parts) = fleet.iter().find(|ship| ship.contains(&at)) else {
+                continue;
+            };
+            if let Some(maneuver) = self.choose_maneuver(parts) {
+                return Some(Action::Move(at, maneuver));
+            }
+        }
+    }
+    // how the picked ship moves, None to pick another one
+    fn choose_maneuver(&self, parts: &[Point]) -> Option<Maneuver> {
+        let title = "arrows to move, r to rotate, esc to pick another";
+        loop {
+            let self_board = BoardView::new(self.board.get_grid(), None, title).with_marked(parts);
+            let opponent_board = BoardView::new(self.opponent_board.get_grid(), None, "");
+            self.render_boards(&opponent_board, &self_board);
+            let event = crossterm::event::read();
+            let Ok(Event::Key(e)) = event else { continue };
+            let maneuver = match e.code {
+                KeyCode::Up => Maneuver::Up,
+                KeyCode::Down => Maneuver::Down,
+                KeyCode::Left => Maneuver::Left,
+                KeyCode::Right => Maneuver::Right,
+                KeyCode::Char('r') => Maneuver::Rotate,
+                KeyCode::Esc => return None,
+                _ => continue,
+            };
+            // a maneuver that doesn't fit is ignored, another one can be tried
+            if self.board.can_move_ship(parts[0], maneuver).is_ok() {
+                return Some(maneuver);
+            }
+        }
+    }
+    // picks a hit on our own ships to patch up, None to go back to firing
+    fn choose_repair(&self) -> Option<Action> {
+        let mut pos = Point::new(0, 0);
+        loop {
+            let at = self.pick_own_cell(&mut pos, "pick a hit to repair, esc to fire")?;
+            if self.board.can_repair_ship(at).is_ok() {
+                return Some(Action::Repair(at));
+            }
+        }
+    }
+}
+// the next weapon with charges left, a normal shot comes after the last one
+fn next_weapon(current: Option<Weapon>, arsenal: &Arsenal) -> Option<Weapon> {
+    let options: Vec<Option<Weapon>> = [None]
+        .into_iter()
+        .chain(
+            Weapon::ALL
+                .into_iter()
+                .filter(|weapon| arsenal.charges(*weapon) > 0)
+                .map(Some),
+        )
+        .collect();
+    let i = options.iter().position(|o| *o == current).unwrap_or(0);
+    options[(i + 1) % options.len()]
 }
 impl GamePlayer for Player {
-    fn choose_point(&mut self) -> Point {
-        let last_pos = match self.last_cursor_pos {
-            Some(pos) => pos,
-            None => Point::new(0, 0),
-        };
-        let mut opponent_board = BoardView::new(
-            self.opponent_board.get_grid(),
-            Some(Cursor::new(last_pos.x, last_pos.y, WIDTH, HEIGHT)),
-            "choose a point",
-        );
+    fn choose_point(&mut self) -> Action {
+        self.choose_action(&Arsenal::default())
+    }
+    fn choose_action(&mut self, arsenal: &Arsenal) -> Action {
+        // None is a normal shot
+        let mut weapon: Option<Weapon> = None;
+        let mut pos = self.last_cursor_pos.unwrap_or(Point::new(0, 0));
+        // the other things the turn can be spent on
+        let mut instead = String::new();
+        if self.board.allows_moves() {
+            instead.push_str(", m to move");
+        }
+        if self.board.allows_repairs() {
+            instead.push_str(", r to repair");
+        }
         loop {
+            let title = match weapon {
+                None if arsenal.is_empty() => format!("choose a point{instead}, esc to quit"),
+                None => format!("shot, tab for weapons{instead}, esc to quit"),
+                Some(weapon) => format!(
+                    "{weapon} ({} left), tab to switch{instead}, esc to quit",
+                    arsenal.charges(weapon)
+                ),
+            };
+            let (width, height) = (self.opponent_board.width(), self.opponent_board.height());
+            // the cells the weapon would reach from where the cursor is
+            let area = weapon.map_or(Vec::new(), |weapon| weapon.area(pos, width, height));
+            let cursor = Cursor::new(pos.x, pos.y, width, height);
+            let mut opponent_board =
+                BoardView::new(self.opponent_board.get_grid(), Some(cursor), &title)
+                    .with_marked(&area);
             self.render_view(&opponent_board);
             let event = crossterm::event::read();
             let Ok(Event::Key(e)) = event else { continue };
+            if e.code == KeyCode::Tab {
+                weapon = next_weapon(weapon, arsenal);
+                continue;
+            }
+            let instead = match e.code {
+                KeyCode::Char('m') if self.board.allows_moves() => Some(self.choose_move()),
+                KeyCode::Char('r') if self.board.allows_repairs() => Some(self.choose_repair()),
+                _ => None,
+            };
+            match instead {
+                Some(Some(action)) => return action,
+                Some(None) => continue,
+                None => {}
+            }
             let res = opponent_board.handle_key(e);
+            pos = opponent_board.cursor_pos().unwrap_or(pos);
             match res {
+                Err(BoardError::Shot(ShotError::ShotExit)) => {
+                    if self.confirm_quit() {
+                        return Action::SaveAndQuit;
+                    }
+                }
                 Err(BoardError::Shot(err)) => {
                     panic!("{}", err);
                 }
@@ -94,20 +259,99 @@ impl GamePlayer for Player {
                 Ok(None) => continue,
                 Ok(Some(placement)) => {
                     self.last_cursor_pos = Some(placement);
-                    return placement;
+                    return match weapon {
+                        Some(weapon) => Action::Strike(weapon, placement),
+                        None => Action::Shoot(placement),
+                    };
                 }
             }
         }
     }
+    fn choose_salvo(&mut self, count: usize) -> Action {
+        let count = count.min(self.opponent_board.targets());
+        let mut targets: Vec<Point> = Vec::new();
+        let mut pos = self.last_cursor_pos.unwrap_or(Point::new(0, 0));
+        while targets.len() < count {
+            let title = format!(
+                "salvo: {}/{count} targets, enter to pick, esc to quit",
+                targets.len()
+            );
+            let cursor = Cursor::new(
+                pos.x,
+                pos.y,
+                self.opponent_board.width(),
+                self.opponent_board.height(),
+            );
+            let mut opponent_board =
+                BoardView::new(self.opponent_board.get_grid(), Some(cursor), &title)
+                    .with_marked(&targets);
+            self.render_view(&opponent_board);
+            let event = crossterm::event::read();
+            let Ok(Event::Key(e)) = event else { continue };
+            let res = opponent_board.handle_key(e);
+            pos = opponent_board.cursor_pos().unwrap_or(pos);
+            match res {
+                Err(BoardError::Shot(ShotError::ShotExit)) => {
+                    if self.confirm_quit() {
+                        return Action::SaveAndQuit;
+                    }
+                }
+                // picking a target again takes it back
+                Ok(Some(target)) if targets.contains(&target) => {
+                    targets.retain(|p| *p != target);
+                }
+                Ok(Some(target))
+                    if self
+                        .opponent_board
+                        .tile(target)
+                        .is_some_and(Tile::is_target) =>
+                {
+                    targets.push(target);
+                }
+                _ => continue,
+            }
+        }
+        self.last_cursor_pos = Some(pos);
+        Action::Salvo(targets)
+    }
     fn is_game_over(&self) -> bool {
         self.board.is_game_over()
     }
-    fn process_shot(&mut self, p: Point) -> Result<ShotResult, BoardError> {
+    fn ships_afloat(&self) -> usize {
+        self.board.ships_afloat()
+    }
+    fn process_shot(&mut self, p: Point) -> Result<ShotResult<'_>, BoardError> {
         self.board.process_shot(p)
     }
     fn update_view_board(&mut self, shot: ShotResult, p: Point) -> Result<(), BoardError> {
         self.opponent_board.register_shot(shot, p)
     }
+    fn process_strike(&mut self, weapon: Weapon, p: Point) -> Result<Strike, BoardError> {
+        self.board.process_strike(weapon, p)
+    }
+    fn update_view_strike(
+        &mut self,
+        weapon: Weapon,
+        p: Point,
+        strike: &Strike,
+    ) -> Result<(), BoardError> {
+        self.opponent_board.register_strike(weapon, p, strike)
+    }
+    fn take_blast(&mut self, p: Point) -> Option<(Point, ShotOutcome)> {
+        self.board.process_blast(p)
+    }
+    fn move_ship(&mut self, at: Point, maneuver: Maneuver) -> Result<usize, BoardError> {
+        self.board.move_ship(at, maneuver)
+    }
+    fn update_view_move(&mut self, _moved: Option<(usize, Maneuver)>) {
+        self.opponent_board.register_move();
+    }
+    fn repair_ship(&mut self, p: Point) -> Result<(), BoardError> {
+        self.board.repair_ship(p)
+    }
+    fn update_view_repair(&mut self, p: Point) -> Result<(), BoardError> {
+        self.opponent_board.register_repair(p)
+    }
     fn get_name(&self) -> &String {
         &self.name
     }
@@ -115,25 +359,71 @@ impl GamePlayer for Player {
         let opponent_board = BoardView::new(self.opponent_board.get_grid(), None, "");
         self.render_view(&opponent_board);
     }
+    fn fleet(&self) -> Option<Vec<Vec<Point>>> {
+        Some(self.board.fleet())
+    }
+    fn decoys(&self) -> Vec<usize> {
+        self.board.decoys()
+    }
+    fn show_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    fn save(&mut self) -> Option<SavedPlayer> {
+        Some(SavedPlayer::Human(HumanState {
+            board: self.board.clone(),
+            opponent_board: self.opponent_board.clone(),
+            last_cursor_pos: self.last_cursor_pos,
+            name: self.name.clone(),
+        }))
+    }
+}
+impl Setup<GameConfig> for Player {
+    fn setup(&mut self, config: GameConfig) {
+        let (width, height) = (config.rules.width, config.rules.height);
+        let no_touch = config.rules.no_touch;
+        self.board = PlayerBoard::new(width, height)
+            .with_no_touch(no_touch)
+            .with_moving(config.rules.moving)
+            .with_repairing(config.rules.repair)
+            .with_map(&config.map);
+        self.opponent_board = ViewBoard::new(width, height)
+            .with_no_touch(no_touch)
+            .with_map(&config.map);
+        self.last_cursor_pos = None;
+        place_fleet(&self.terminal, &mut self.board, &config.fleet, self.seed);
+    }
 }
-impl Setup<Vec<ShipBlueprint>> for Player {
-    fn setup(&mut self, ships: Vec<ShipBlueprint>) {
-        for ship in ships.iter() {
-            // TODO: add selecting of coordinates to put the ship
 
-            let mut builder = BoardBuilder::new(&self.board, ship);
-            let (pos, rot) = loop {
-                self.terminal
-                    .borrow_mut()
-                    .draw(|f| builder.render(f, f.area()));
-                let event = crossterm::event::read();
-                let Ok(Event::Key(e)) = event else { continue };
-                let res = builder.handle_key(e);
-                let Ok(Some(placement)) = res else { continue };
-                break placement;
-            };
-            // this wont ever be an error
-            self.board.place_ship(ship, pos, rot).unwrap();
-        }
+// lets the player put every ship of the fleet on their board one by one, the seed of the game is
+// shown along when it is known. Esc takes every ship off again, for when the ones placed so far
+// leave no room for the next
+pub fn place_fleet(
+    terminal: &RefCell<ratatui::DefaultTerminal>,
+    board: &mut PlayerBoard,
+    fleet: &[ShipBlueprint],
+    seed: Option<u64>,
+) {
+    let empty = board.clone();
+    let mut placed = 0;
+    while let Some(ship) = fleet.get(placed) {
+        let mut builder = BoardBuilder::new(board, ship).with_seed(seed);
+        let placement = loop {
+            let _ = terminal.borrow_mut().draw(|f| builder.render(f, f.area()));
+            let event = crossterm::event::read();
+            let Ok(Event::Key(e)) = event else { continue };
+            match builder.handle_key(e) {
+                Ok(Some(placement)) => break Some(placement),
+                Err(BoardError::ShipPlacementError(PlacementError::PlacementExit)) => break None,
+                _ => continue,
+            }
+        };
+        let Some((pos, rot)) = placement else {
+            *board = empty.clone();
+            placed = 0;
+            continue;
+        };
+        // this wont ever be an error
+        board.place_ship(ship, pos, rot).unwrap();
+        placed += 1;
     }
 }
@@ -0,0 +1,530 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::game::{
+    Setup,
+    config::{GameConfig, Map},
+    net::{
+        Connection, NetError,
+        commitment::{self, Commitment, RevealedMove, TamperError},
+        protocol::Message,
+    },
+    player_board::{BoardError, MoveError, ShotError, ShotOutcome, ShotResult},
+    players::{Action, GamePlayer},
+    point::Point,
+    ship::{Maneuver, ShipBlueprint},
+    weapon::{Arsenal, Strike, Weapon},
+};
+
+// The opponent on the other end of a network game. Its board lives on the other machine, this
+// side only remembers what it was told about it.
+pub struct RemotePlayer {
+    conn: Connection,
+    name: String,
+    width: usize,
+    height: usize,
+    fleet: Vec<ShipBlueprint>,
+    no_touch: bool,
+    map: Map,
+    moving: bool,
+    repair: bool,
+    sunk: usize,
+    shot: HashSet<Point>,
+    // every answer the remote side gave, checked against their fleet once it is revealed
+    results: Vec<(Point, ShotOutcome)>,
+    // every radar ping and how many ship cells the remote side said it found
+    scans: Vec<(Point, usize)>,
+    // the hash of their fleet after every move, with how many answers and pings came before it
+    moved: Vec<(String, usize, usize)>,
+    // every cell they repaired, with how many answers came before it
+    repairs: Vec<(usize, Point)>,
+    // what the last shot at the remote board did, process_shot hands out a reference into it
+    last_result: ShotOutcome,
+    // our fleet, hidden until the game is over
+    ours: Option<Commitment>,
+    // which of our ships are decoys, told along with the fleet
+    our_decoys: Vec<usize>,
+    theirs: String,
+    // their fleet, once it was revealed and checked
+    revealed: Option<Vec<Vec<Point>>>,
+    revealed_moves: Option<Vec<(usize, Maneuver)>>,
+    // what ended the connection, nothing is sent or received after it
+    lost: Option<NetError>,
+    // an answer no fleet could have given, nothing is sent or received after it either
+    caught: Option<TamperError>,
+}
+impl RemotePlayer {
+    pub fn new(conn: Connection, name: String) -> Self {
+        Self {
+            conn,
+            name,
+            width: 0,
+            height: 0,
+            fleet: Vec::new(),
+            no_touch: false,
+            map: Map::default(),
+            moving: false,
+            repair: false,
+            sunk: 0,
+            shot: HashSet::new(),
+            results: Vec::new(),
+            scans: Vec::new(),
+            moved: Vec::new(),
+            repairs: Vec::new(),
+            last_result: ShotOutcome::Miss,
+            ours: None,
+            our_decoys: Vec::new(),
+            theirs: String::new(),
+            revealed: None,
+            revealed_moves: None,
+            lost: None,
+            caught: None,
+        }
+    }
+    // only the first error is kept, it's what ended the connection
+    fn lose(&mut self, err: NetError) {
+        self.lost.get_or_insert(err);
+    }
+    fn send(&mut self, message: &Message) {
+        if self.gone().is_some() {
+            return;
+        }
+        if let Err(err) = self.conn.send(message) {
+            self.lose(err);
+        }
+    }
+    // None once the connection is lost
+    fn recv(&mut self) -> Option<Message> {
+        if self.gone().is_some() {
+            return None;
+        }
+        self.conn.recv().map_err(|err| self.lose(err)).ok()
+    }
+    // a message that doesn't fit the game, the connection is given up on
+    fn unexpected(&mut self, message: Option<Message>) {
+        if let Some(message) = message {
+            self.lose(NetError::Unexpected(format!("{message:?}")));
+        }
+    }
+    // the remote side ignores invalid shots without answering, so they never leave here
+    fn check_target(&mut self, p: Point) -> Result<(), BoardError> {
+        if p.x >= self.width || p.y >= self.height {
+            return Err(BoardError::Shot(ShotError::OutOfBounds));
+        }
+        if self.map.islands.contains(&p) {
+            return Err(BoardError::Shot(ShotError::Island));
+        }
+        if !self.shot.insert(p) {
+            return Err(BoardError::Shot(ShotError::AlreadyShot));
+        }
+        Ok(())
+    }
+    // every answer is checked as it comes in, a lie caught here never reaches the view board
+    fn answered(&mut self, p: Point, result: ShotOutcome) -> Result<(), BoardError> {
+        if let Err(err) = self.check_answer(p, &result) {
+            self.caught = Some(err);
+            return Err(BoardError::Gone);
+        }
+        match result {
+            // a decoy going down brings them no closer to losing
+            ShotOutcome::Sunk { .. } => self.sunk += 1,
+            // an armored part that held can be shot again
+            ShotOutcome::Damaged => {
+                self.shot.remove(&p);
+            }
+            _ => {}
+        }
+        self.results.push((p, result));
+        Ok(())
+    }
+    // a sunk ship is on the board, was hit at p and had every other part destroyed before, and
+    // there is a ship left to sink
+    fn check_answer(&self, p: Point, result: &ShotOutcome) -> Result<(), TamperError> {
+        let parts = match result {
+            ShotOutcome::Sunk { parts } | ShotOutcome::Decoy { parts } => parts,
+            _ => return Ok(()),
+        };
+        let on_board = |q: &Point| q.x < self.width && q.y < self.height;
+        let too_many = matches!(result, ShotOutcome::Sunk { .. }) && self.sunk >= self.real_ships();
+        if too_many
+            || !parts.contains(&p)
+            || !parts.iter().all(on_board)
+            || !parts.iter().all(|&q| q == p || self.destroyed(q))
+        {
+            return Err(TamperError::ImpossibleResult {
+                at: p,
+                reported: result.clone(),
+            });
+        }
+        Ok(())
+    }
+    // one of their ships moved, where it was shot and missed may hold a ship again
+    fn ship_moved(&mut self, hash: String) {
+        self.moved
+            .push((hash, self.results.len(), self.scans.len()));
+        let mut last = HashMap::new();
+        for (p, result) in &self.results {
+            last.insert(*p, result);
+        }
+        self.shot.retain(|p| {
+            last.get(p)
+                .is_none_or(|result| **result != ShotOutcome::Miss)
+        });
+    }
+    // they said the part at p was destroyed, it wasn't repaired since and its ship didn't sink
+    fn destroyed(&self, p: Point) -> bool {
+        let last =
+            self.results
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(i, (at, result))| match result {
+                    ShotOutcome::Sunk { parts } | ShotOutcome::Decoy { parts }
+                        if parts.contains(&p) =>
+                    {
+                        Some(None)
+                    }
+                    _ if *at == p => Some((*result == ShotOutcome::Hit).then_some(i)),
+                    _ => None,
+                });
+        last.flatten().is_some_and(|hit| {
+            !self
+                .repairs
+                .iter()
+                .any(|(after, at)| *at == p && *after > hit)
+        })
+    }
+    // a repair is only believed on a cell they said was destroyed, of a ship they never said sank
+    fn can_repair(&self, p: Point) -> bool {
+        self.destroyed(p) && !self.repairs.iter().any(|(_, at)| *at == p)
+    }
+    // the ships that have to be sunk to win
+    fn real_ships(&self) -> usize {
+        self.fleet.iter().filter(|ship| !ship.decoy).count()
+    }
+}
+fn reveal_move(m: &RevealedMove) -> (usize, Maneuver) {
+    (m.ship, m.maneuver)
+}
+
+impl GamePlayer for RemotePlayer {
+    fn choose_point(&mut self) -> Action {
+        match self.recv() {
+            Some(Message::Shot { at }) => Action::Shoot(at),
+            other => {
+                self.unexpected(other);
+                Action::Forfeit
+            }
+        }
+    }
+    fn choose_action(&mut self, _arsenal: &Arsenal) -> Action {
+        match self.recv() {
+            Some(Message::Shot { at }) => Action::Shoot(at),
+            Some(Message::Strike { weapon, at }) => Action::Strike(weapon, at),
+            Some(Message::Moved { hash }) if self.moving => {
+                self.ship_moved(hash);
+                Action::Moved
+            }
+            Some(Message::Repaired { at }) if self.repair && self.can_repair(at) => {
+                Action::Repair(at)
+            }
+            other => {
+                self.unexpected(other);
+                Action::Forfeit
+            }
+        }
+    }
+    fn choose_salvo(&mut self, count: usize) -> Action {
+        match self.recv() {
+            Some(Message::Salvo { shots }) if shots.len() <= count => Action::Salvo(shots),
+            other => {
+                self.unexpected(other);
+                Action::Forfeit
+            }
+        }
+    }
+    fn is_game_over(&self) -> bool {
+        self.sunk >= self.real_ships()
+    }
+    fn ships_afloat(&self) -> usize {
+        self.real_ships().saturating_sub(self.sunk)
+    }
+    fn process_shot(&mut self, p: Point) -> Result<ShotResult<'_>, BoardError> {
+        self.check_target(p)?;
+        self.send(&Message::Shot { at: p });
+        self.last_result = match self.recv() {
+            Some(Message::Result { at, result }) if at == p => result,
+            other => {
+                self.unexpected(other);
+                return Err(BoardError::Gone);
+            }
+        };
+        self.answered(p, self.last_result.clone())?;
+        Ok(self.last_result.as_shot())
+    }
+    fn process_salvo(&mut self, points: &[Point]) -> Vec<(Point, Result<ShotOutcome, BoardError>)> {
+        let checked: Vec<(Point, Result<(), BoardError>)> =
+            points.iter().map(|&p| (p, self.check_target(p))).collect();
+        let shots: Vec<Point> = checked
+            .iter()
+            .filter(|(_, res)| res.is_ok())
+            .map(|(p, _)| *p)
+            .collect();
+        self.send(&Message::Salvo {
+            shots: shots.clone(),
+        });
+        let results = match self.recv() {
+            Some(Message::Results { results })
+                if results.iter().map(|(p, _)| *p).eq(shots.iter().copied()) =>
+            {
+                results
+            }
+            other => {
+                self.unexpected(other);
+                return points.iter().map(|&p| (p, Err(BoardError::Gone))).collect();
+            }
+        };
+        for (p, result) in &results {
+            if self.answered(*p, result.clone()).is_err() {
+                return points.iter().map(|&p| (p, Err(BoardError::Gone))).collect();
+            }
+        }
+        let mut results = results.into_iter();
+        checked
+            .into_iter()
+            .map(|(p, res)| {
+                let res = res.map(|()| {
+                    let (_, result) = results.next().expect("every valid shot was answered");
+                    result
+                });
+                (p, res)
+            })
+            .collect()
+    }
+    fn process_strike(&mut self, weapon: Weapon, p: Point) -> Result<Strike, BoardError> {
+        if p.x >= self.width || p.y >= self.height {
+            return Err(BoardError::Shot(ShotError::OutOfBounds));
+        }
+        let area = weapon.area(p, self.width, self.height);
+        let fresh: Vec<Point> = area
+            .iter()
+            .copied()
+            .filter(|q| !self.shot.contains(q) && !self.map.islands.contains(q))
+            .collect();
+        // a strike that reaches nothing new is ignored like a shot at a cell already shot
+        if weapon != Weapon::Radar && fresh.is_empty() {
+            return Err(BoardError::Shot(ShotError::AlreadyShot));
+        }
+        self.send(&Message::Strike { weapon, at: p });
+        let strike = match self.recv() {
+            Some(Message::Struck { at, result }) if at == p => result,
+            other => {
+                self.unexpected(other);
+                return Err(BoardError::Gone);
+            }
+        };
+        match &strike {
+            Strike::Ping { count } if weapon == Weapon::Radar && *count <= area.len() => {
+                self.scans.push((p, *count));
+            }
+            Strike::Shots { results } if weapon.fired_at(&fresh, results) => {
+                for (q, result) in results {
+                    self.shot.insert(*q);
+                    self.answered(*q, result.clone())?;
+                }
+            }
+            other => {
+                self.lose(NetError::Unexpected(format!("{other:?}")));
+                return Err(BoardError::Gone);
+            }
+        }
+        Ok(strike)
+    }
+    // the remote player set off one of our mines, they tell us what the blast did to their fleet
+    fn take_blast(&mut self, _p: Point) -> Option<(Point, ShotOutcome)> {
+        let (at, result) = match self.recv() {
+            Some(Message::Blast { at, result })
+                if at.x < self.width
+                    && at.y < self.height
+                    && !self.map.cells().any(|q| *q == at) =>
+            {
+                (at, result)
+            }
+            other => {
+                self.unexpected(other);
+                return None;
+            }
+        };
+        self.shot.insert(at);
+        self.answered(at, result.clone()).ok()?;
+        Some((at, result))
+    }
+    // their ships move on their side, see choose_action
+    fn move_ship(&mut self, _at: Point, _maneuver: Maneuver) -> Result<usize, BoardError> {
+        Err(BoardError::ShipMoveError(MoveError::NotAllowed))
+    }
+    fn update_view_move(&mut self, moved: Option<(usize, Maneuver)>) {
+        let (ship, maneuver) = moved.expect("the other player of a network game is local");
+        let hash = self
+            .ours
+            .as_mut()
+            .expect("fleets are committed to before the first turn")
+            .moved(ship, maneuver);
+        self.send(&Message::Moved { hash });
+    }
+    // the repair was checked when it came in, see choose_action
+    fn repair_ship(&mut self, p: Point) -> Result<(), BoardError> {
+        self.repairs.push((self.results.len(), p));
+        self.shot.remove(&p);
+        Ok(())
+    }
+    fn update_view_repair(&mut self, p: Point) -> Result<(), BoardError> {
+        self.send(&Message::Repaired { at: p });
+        Ok(())
+    }
+    fn update_view_board(&mut self, shot: ShotResult, p: Point) -> Result<(), BoardError> {
+        // the remote player shot at us, tell them what they hit
+        self.send(&Message::Result {
+            at: p,
+            result: ShotOutcome::from(&shot),
+        });
+        Ok(())
+    }
+    fn update_view_salvo(&mut self, results: &[(Point, ShotOutcome)]) -> Result<(), BoardError> {
+        self.send(&Message::Results {
+            results: results.to_vec(),
+        });
+        Ok(())
+    }
+    fn update_view_strike(
+        &mut self,
+        _weapon: Weapon,
+        p: Point,
+        strike: &Strike,
+    ) -> Result<(), BoardError> {
+        self.send(&Message::Struck {
+            at: p,
+            result: strike.clone(),
+        });
+        Ok(())
+    }
+    fn update_view_blast(&mut self, p: Point, result: &ShotOutcome) -> Result<(), BoardError> {
+        self.send(&Message::Blast {
+            at: p,
+            result: result.clone(),
+        });
+        Ok(())
+    }
+    fn get_name(&self) -> &String {
+        &self.name
+    }
+    fn render(&self) {}
+    fn commit(&mut self, opponent_fleet: Option<Vec<Vec<Point>>>, opponent_decoys: Vec<usize>) {
+        let fleet = opponent_fleet.expect("the other player of a network game is local");
+        let ours = Commitment::new(fleet);
+        self.send(&Message::Commit { hash: ours.hash() });
+        self.ours = Some(ours);
+        self.our_decoys = opponent_decoys;
+        match self.recv() {
+            Some(Message::Commit { hash }) => self.theirs = hash,
+            other => self.unexpected(other),
+        }
+    }
+    fn gone(&self) -> Option<String> {
+        if self.caught.is_some() {
+            return Some(String::from("they were caught lying"));
+        }
+        self.lost
+            .as_ref()
+            .map(|err| format!("the connection was lost, {err}"))
+    }
+    fn caught(&mut self) -> Option<TamperError> {
+        self.caught.take()
+    }
+    fn verify(&mut self) -> Result<(), TamperError> {
+        if let Some(ours) = &self.ours {
+            let reveal = Message::Reveal {
+                salt: ours.salt(),
+                fleet: ours.fleet().to_vec(),
+                decoys: self.our_decoys.clone(),
+                moves: ours.moves(),
+            };
+            self.conn.send(&reveal).map_err(TamperError::NoReveal)?;
+        }
+        let (salt, fleet, decoys, moves) = match self.conn.recv().map_err(TamperError::NoReveal)? {
+            Message::Reveal {
+                salt,
+                fleet,
+                decoys,
+                moves,
+            } => (salt, fleet, decoys, moves),
+            other => {
+                return Err(TamperError::NoReveal(NetError::Unexpected(format!(
+                    "{other:?}"
+                ))));
+            }
+        };
+        commitment::check_commitment(&self.theirs, &salt, &fleet)?;
+        let legal = |fleet: &[Vec<Point>]| {
+            commitment::check_fleet(
+                fleet,
+                self.width,
+                self.height,
+                &self.fleet,
+                self.no_touch,
+                &self.map,
+            )
+        };
+        legal(&fleet)?;
+        commitment::check_decoys(&fleet, &decoys, &self.fleet)?;
+        let hashes: Vec<String> = self.moved.iter().map(|(hash, ..)| hash.clone()).collect();
+        let fleets = commitment::check_moves(&fleet, &moves, &hashes, legal)?;
+        let moved_after: Vec<usize> = self.moved.iter().map(|(_, shots, _)| *shots).collect();
+        commitment::check_shots(
+            &fleets,
+            &moved_after,
+            &self.repairs,
+            &decoys,
+            &self.fleet,
+            &self.map.mines,
+            &self.results,
+        )?;
+        // every ping is checked against where the fleet was at the time
+        let scans_until = self.moved.iter().map(|(.., scans)| *scans);
+        let mut from = 0;
+        for (fleet, until) in fleets.iter().zip(scans_until.chain([self.scans.len()])) {
+            commitment::check_scans(fleet, self.width, self.height, &self.scans[from..until])?;
+            from = until;
+        }
+        self.revealed = Some(fleet);
+        self.revealed_moves = Some(moves.iter().map(reveal_move).collect());
+        Ok(())
+    }
+    fn fleet(&self) -> Option<Vec<Vec<Point>>> {
+        self.revealed.clone()
+    }
+    fn moves(&self) -> Option<Vec<(usize, Maneuver)>> {
+        self.revealed_moves.clone()
+    }
+}
+impl Setup<GameConfig> for RemotePlayer {
+    fn setup(&mut self, config: GameConfig) {
+        self.width = config.rules.width;
+        self.height = config.rules.height;
+        self.fleet = config.fleet;
+        self.no_touch = config.rules.no_touch;
+        self.map = config.map;
+        self.moving = config.rules.moving;
+        self.repair = config.rules.repair;
+        self.sunk = 0;
+        self.shot.clear();
+        self.results.clear();
+        self.scans.clear();
+        self.moved.clear();
+        self.repairs.clear();
+        self.revealed = None;
+        self.revealed_moves = None;
+        self.lost = None;
+        self.caught = None;
+        // the fleets are exchanged as commitments once ours is placed, see commit
+    }
+}
@@ -1,4 +1,7 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "(usize, usize)", into = "(usize, usize)")]
 pub struct Point {
     pub x: usize,
     pub y: usize,
@@ -7,4 +10,28 @@ impl Point {
     pub fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
+    // returns None when the move would go below zero, the upper bounds are up to the board
+    pub fn offset(self, dx: isize, dy: isize) -> Option<Point> {
+        Some(Self::new(
+            self.x.checked_add_signed(dx)?,
+            self.y.checked_add_signed(dy)?,
+        ))
+    }
+    // the up to eight cells around this one, diagonals included
+    pub fn neighbours(self) -> impl Iterator<Item = Point> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| (dx, dy) != (0, 0))
+            .filter_map(move |(dx, dy)| self.offset(dx, dy))
+    }
+}
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self::new(x, y)
+    }
+}
+impl From<Point> for (usize, usize) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
 }
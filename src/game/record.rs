@@ -0,0 +1,956 @@
+use std::error::Error;
+use std::fmt::{Display, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    CurrentPlayer,
+    config::{GameConfig, Map},
+    player_board::ShotOutcome,
+    point::Point,
+    save,
+    ship::{Maneuver, ShipBlueprint},
+    tile::Tile,
+};
+
+// Bump this whenever the record format changes.
+const RECORD_VERSION: u32 = 9;
+const HEADER: &str = "battleships-record";
+
+// A finished or abandoned game, written as text so it can be read and diffed by hand. One entry
+// per line, the keyword first:
+//
+//   battleships-record 9          format version, always the first line
+//   seed 42                       the game seed
+//   started 1760780000            unix time the game started at, in seconds
+//   board 10 10                   width and height
+//   blueprint 0,0;0,1 2,1 ship new
+//                                 a ship of the fleet, its cells, the hits each part takes or
+//                                 - when it's one each, ship or decoy, and then its name
+//   island 4,4;4,5                islands of the map, both boards have the same
+//   mine 7,2                      mines of the map
+//   player 1 mistr                the name of player 1 or 2
+//   ship 1 3,4;3,5;4,5            where a ship of player 1 or 2 was placed, missing when unknown
+//   shot 1250 1 3,4 hit           ms since the start, who shot, where, and hit, damaged, miss
+//                                 or sunk, damaged when an armored part held, mine when it
+//                                 set one off, the blast is the next shot by the other player
+//   shot 2400 2 0,0 sunk 0,0;0,1  a sinking shot lists the cells of the ship it sank
+//   shot 2600 1 7,7 decoy 7,7;8,7 and one that sank a decoy lists the cells of the decoy
+//   radar 3100 1 5,5 2            a radar ping, and how many ship cells it found
+//   move 4000 2 12 1 rotate       a ship moved after the first 12 shots, its place in the fleet
+//                                 and how it moved, both missing while they are unknown
+//   repair 5200 1 14 3,4          a hit was repaired after the first 14 shots
+//   winner 1                      only there when the game was finished
+//
+// Empty lines and lines starting with # are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub started: u64,
+    pub width: usize,
+    pub height: usize,
+    pub fleet: Vec<ShipBlueprint>,
+    #[serde(default)]
+    pub map: Map,
+    pub players: [RecordedPlayer; 2],
+    pub shots: Vec<RecordedShot>,
+    #[serde(default)]
+    pub scans: Vec<RecordedScan>,
+    #[serde(default)]
+    pub moves: Vec<RecordedMove>,
+    #[serde(default)]
+    pub repairs: Vec<RecordedRepair>,
+    pub winner: Option<CurrentPlayer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RecordedPlayer {
+    pub name: String,
+    // None when the ships were never revealed
+    pub layout: Option<Vec<Vec<Point>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedShot {
+    // milliseconds since the game started
+    pub time: u64,
+    pub player: CurrentPlayer,
+    pub at: Point,
+    pub result: ShotOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedScan {
+    pub time: u64,
+    pub player: CurrentPlayer,
+    pub at: Point,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedMove {
+    pub time: u64,
+    pub player: CurrentPlayer,
+    // how many shots were fired before it
+    pub after: usize,
+    // the ship's place in the fleet and how it moved, None until the player revealed it
+    pub moved: Option<(usize, Maneuver)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRepair {
+    pub time: u64,
+    pub player: CurrentPlayer,
+    // how many shots were fired before it
+    pub after: usize,
+    pub at: Point,
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(std::io::Error),
+    Version(u32),
+    // the line number, starting at 1, and what is wrong with it
+    Line(usize, String),
+    // the lines are fine but don't describe a game together
+    Invalid(String),
+}
+impl Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not access the record: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "the record has format version {version}, this game reads {RECORD_VERSION}"
+            ),
+            Self::Line(line, why) => write!(f, "line {line}: {why}"),
+            Self::Invalid(why) => write!(f, "the record is broken: {why}"),
+        }
+    }
+}
+impl Error for RecordError {}
+impl From<std::io::Error> for RecordError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+impl GameRecord {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            seed: config.seed,
+            started: unix_secs(SystemTime::now()),
+            width: config.rules.width,
+            height: config.rules.height,
+            fleet: config.fleet.clone(),
+            map: config.map.clone(),
+            players: Default::default(),
+            shots: Vec::new(),
+            scans: Vec::new(),
+            moves: Vec::new(),
+            repairs: Vec::new(),
+            winner: None,
+        }
+    }
+    pub fn player(&self, player: CurrentPlayer) -> &RecordedPlayer {
+        &self.players[player as usize - 1]
+    }
+    pub fn player_mut(&mut self, player: CurrentPlayer) -> &mut RecordedPlayer {
+        &mut self.players[player as usize - 1]
+    }
+    // milliseconds since the game started
+    fn now(&self) -> u64 {
+        let started = UNIX_EPOCH + std::time::Duration::from_secs(self.started);
+        SystemTime::now()
+            .duration_since(started)
+            .map_or(0, |d| d.as_millis() as u64)
+    }
+    pub fn shot(&mut self, player: CurrentPlayer, at: Point, result: ShotOutcome) {
+        let time = self.now();
+        self.shots.push(RecordedShot {
+            time,
+            player,
+            at,
+            result,
+        });
+    }
+
+    pub fn scan(&mut self, player: CurrentPlayer, at: Point, count: usize) {
+        let time = self.now();
+        self.scans.push(RecordedScan {
+            time,
+            player,
+            at,
+            count,
+        });
+    }
+
+    pub fn moved(&mut self, player: CurrentPlayer, moved: Option<(usize, Maneuver)>) {
+        let time = self.now();
+        let after = self.shots.len();
+        self.moves.push(RecordedMove {
+            time,
+            player,
+            after,
+            moved,
+        });
+    }
+    pub fn repaired(&mut self, player: CurrentPlayer, at: Point) {
+        let time = self.now();
+        let after = self.shots.len();
+        self.repairs.push(RecordedRepair {
+            time,
+            player,
+            after,
+            at,
+        });
+    }
+    // fills in the moves of a player that were hidden during the game, in the order they were made
+    pub fn reveal_moves(&mut self, player: CurrentPlayer, revealed: &[(usize, Maneuver)]) {
+        let hidden = self
+            .moves
+            .iter_mut()
+            .filter(|m| m.player == player && m.moved.is_none());
+        for (recorded, moved) in hidden.zip(revealed) {
+            recorded.moved = Some(*moved);
+        }
+    }
+
+    // the board of a player after the first `step` shots of the game
+    pub fn board_at(&self, player: CurrentPlayer, step: usize) -> Vec<Vec<Tile>> {
+        let layout = &self.player(player).layout;
+        let blank = if layout.is_some() {
+            Tile::Empty
+        } else {
+            Tile::Hidden
+        };
+        let mut grid = vec![vec![blank; self.width]; self.height];
+        let mut ships = layout.clone().unwrap_or_default();
+        for (id, ship) in ships.iter().enumerate() {
+            for p in ship {
+                grid[p.y][p.x] = Tile::Ship(id as u8);
+            }
+        }
+        for p in &self.map.islands {
+            grid[p.y][p.x] = Tile::Island;
+        }
+        if layout.is_some() {
+            for p in &self.map.mines {
+                grid[p.y][p.x] = Tile::Mine;
+            }
+        }
+        let step = step.min(self.shots.len());
+        let mut moves = self.moves.iter().filter(|m| m.player == player).peekable();
+        let mut repairs = self
+            .repairs
+            .iter()
+            .filter(|r| r.player == player)
+            .peekable();
+        for i in 0..=step {
+            // the moves and repairs made before this shot, or after the last one shown
+            while let Some(m) = moves.next_if(|m| m.after <= i) {
+                move_ship(&mut grid, &mut ships, m.moved, blank);
+            }
+            while let Some(r) = repairs.next_if(|r| r.after <= i) {
+                let at = r.at;
+                let Some(tile) = grid.get_mut(at.y).and_then(|row| row.get_mut(at.x)) else {
+                    continue;
+                };
+                *tile = match ships.iter().position(|ship| ship.contains(&at)) {
+                    Some(id) => Tile::Ship(id as u8),
+                    None => Tile::Repaired,
+                };
+            }
+            let Some(shot) = self.shots[..step].get(i) else {
+                break;
+            };
+            if shot.player == player {
+                continue;
+            }
+            match &shot.result {
+                ShotOutcome::Hit => grid[shot.at.y][shot.at.x] = Tile::Hit,
+                ShotOutcome::Damaged => grid[shot.at.y][shot.at.x] = Tile::Damaged,
+                ShotOutcome::Miss => grid[shot.at.y][shot.at.x] = Tile::Miss,
+                ShotOutcome::Mine => grid[shot.at.y][shot.at.x] = Tile::Exploded,
+                ShotOutcome::Sunk { parts } => {
+                    parts.iter().for_each(|p| grid[p.y][p.x] = Tile::SunkenShip)
+                }
+                ShotOutcome::Decoy { parts } => {
+                    parts.iter().for_each(|p| grid[p.y][p.x] = Tile::Decoy)
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        // writing into a String never fails
+        let _ = writeln!(text, "{HEADER} {RECORD_VERSION}");
+        let _ = writeln!(text, "seed {}", self.seed);
+        let _ = writeln!(text, "started {}", self.started);
+        let _ = writeln!(text, "board {} {}", self.width, self.height);
+        for ship in &self.fleet {
+            let hp = match ship.hp.is_empty() {
+                true => String::from("-"),
+                false => ship
+                    .hp
+                    .iter()
+                    .map(u8::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            };
+            let kind = if ship.decoy { "decoy" } else { "ship" };
+            let _ = writeln!(
+                text,
+                "blueprint {} {hp} {kind} {}",
+                cells(&ship.parts),
+                ship.name
+            );
+        }
+        if !self.map.islands.is_empty() {
+            let _ = writeln!(text, "island {}", cells(&self.map.islands));
+        }
+        if !self.map.mines.is_empty() {
+            let _ = writeln!(text, "mine {}", cells(&self.map.mines));
+        }
+        for (i, player) in self.players.iter().enumerate() {
+            let _ = writeln!(text, "player {} {}", i + 1, player.name);
+            for ship in player.layout.iter().flatten() {
+                let _ = writeln!(text, "ship {} {}", i + 1, cells(ship));
+            }
+        }
+        for shot in &self.shots {
+            let result = match &shot.result {
+                ShotOutcome::Hit => String::from("hit"),
+                ShotOutcome::Damaged => String::from("damaged"),
+                ShotOutcome::Miss => String::from("miss"),
+                ShotOutcome::Mine => String::from("mine"),
+                ShotOutcome::Sunk { parts } => format!("sunk {}", cells(parts)),
+                ShotOutcome::Decoy { parts } => format!("decoy {}", cells(parts)),
+            };
+            let _ = writeln!(
+                text,
+                "shot {} {} {},{} {result}",
+                shot.time, shot.player as u8, shot.at.x, shot.at.y
+            );
+        }
+        for scan in &self.scans {
+            let _ = writeln!(
+                text,
+                "radar {} {} {},{} {}",
+                scan.time, scan.player as u8, scan.at.x, scan.at.y, scan.count
+            );
+        }
+        for m in &self.moves {
+            let _ = write!(text, "move {} {} {}", m.time, m.player as u8, m.after);
+            if let Some((ship, maneuver)) = m.moved {
+                let _ = write!(text, " {ship} {maneuver}");
+            }
+            text.push('\n');
+        }
+        for r in &self.repairs {
+            let _ = writeln!(
+                text,
+                "repair {} {} {} {},{}",
+                r.time, r.player as u8, r.after, r.at.x, r.at.y
+            );
+        }
+        if let Some(winner) = self.winner {
+            let _ = writeln!(text, "winner {}", winner as u8);
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, RecordError> {
+        let mut record = Self {
+            seed: 0,
+            started: 0,
+            width: 0,
+            height: 0,
+            fleet: Vec::new(),
+            map: Map::default(),
+            players: Default::default(),
+            shots: Vec::new(),
+            scans: Vec::new(),
+            moves: Vec::new(),
+            repairs: Vec::new(),
+            winner: None,
+        };
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let Some((n, header)) = lines.next() else {
+            return Err(RecordError::Invalid(String::from("the record is empty")));
+        };
+        let version = match header.split_once(' ') {
+            Some((HEADER, version)) => match version.parse() {
+                // every older format is read too
+                Ok(version @ 1..=RECORD_VERSION) => version,
+                Ok(version) => return Err(RecordError::Version(version)),
+                Err(_) => return Err(RecordError::Line(n, String::from("bad version"))),
+            },
+            _ => return Err(RecordError::Line(n, format!("not a {HEADER}"))),
+        };
+
+        for (n, line) in lines {
+            record
+                .parse_line(line, version)
+                .map_err(|why| RecordError::Line(n, why))?;
+        }
+        if record.width == 0 || record.height == 0 {
+            return Err(RecordError::Invalid(String::from(
+                "the board size is missing",
+            )));
+        }
+        let in_bounds = |p: &Point| p.x < record.width && p.y < record.height;
+        let points_ok = record
+            .players
+            .iter()
+            .flat_map(|player| player.layout.iter().flatten().flatten())
+            .chain(record.shots.iter().flat_map(|shot| match &shot.result {
+                ShotOutcome::Sunk { parts } | ShotOutcome::Decoy { parts } => {
+                    parts.iter().chain([&shot.at]).collect()
+                }
+                _ => vec![&shot.at],
+            }))
+            .chain(record.scans.iter().map(|scan| &scan.at))
+            .chain(record.map.cells())
+            .all(in_bounds);
+        if !points_ok {
+            return Err(RecordError::Invalid(String::from(
+                "a cell is off the board",
+            )));
+        }
+        Ok(record)
+    }
+
+    fn parse_line(&mut self, line: &str, version: u32) -> Result<(), String> {
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let mut fields = rest.split(' ');
+        let mut field = |what: &str| fields.next().ok_or(format!("{what} is missing"));
+        match keyword {
+            "seed" => self.seed = number(field("the seed")?)?,
+            "started" => self.started = number(field("the start time")?)?,
+            "board" => {
+                self.width = number(field("the width")?)?;
+                self.height = number(field("the height")?)?;
+            }
+            "blueprint" => {
+                let (parts, rest) = rest.split_once(' ').ok_or("the name is missing")?;
+                let parts = parse_cells(parts)?;
+                // before version 8 every part took one hit
+                let (hp, rest) = match version {
+                    ..8 => (Vec::new(), rest),
+                    _ => match rest.split_once(' ').ok_or("the name is missing")? {
+                        ("-", rest) => (Vec::new(), rest),
+                        (hp, rest) => (hp.split(',').map(number).collect::<Result<_, _>>()?, rest),
+                    },
+                };
+                // and before version 9 there were no decoys
+                let (decoy, name) = match version {
+                    ..9 => (false, rest),
+                    _ => match rest.split_once(' ').ok_or("the name is missing")? {
+                        ("ship", name) => (false, name),
+                        ("decoy", name) => (true, name),
+                        (other, _) => return Err(format!("'{other}' is not a ship or a decoy")),
+                    },
+                };
+                if !hp.is_empty() && hp.len() != parts.len() {
+                    return Err(String::from("the hit points don't match the cells"));
+                }
+                self.fleet.push(
+                    ShipBlueprint::new(parts, String::from(name))
+                        .with_hp(hp)
+                        .with_decoy(decoy),
+                );
+            }
+            "island" => self.map.islands = parse_cells(field("the cells")?)?,
+            "mine" => self.map.mines = parse_cells(field("the cells")?)?,
+            "player" => {
+                let (player, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                let player = parse_player(player)?;
+                self.player_mut(player).name = String::from(name);
+            }
+            "ship" => {
+                let player = parse_player(field("the player")?)?;
+                let ship = parse_cells(field("the cells")?)?;
+                self.player_mut(player)
+                    .layout
+                    .get_or_insert_with(Vec::new)
+                    .push(ship);
+            }
+            "shot" => {
+                let time = number(field("the time")?)?;
+                let player = parse_player(field("the player")?)?;
+                let at = parse_point(field("the cell")?)?;
+                let result = match field("the result")? {
+                    "hit" => ShotOutcome::Hit,
+                    "damaged" => ShotOutcome::Damaged,
+                    "miss" => ShotOutcome::Miss,
+                    "mine" => ShotOutcome::Mine,
+                    "sunk" => ShotOutcome::Sunk {
+                        parts: parse_cells(field("the sunk cells")?)?,
+                    },
+                    "decoy" => ShotOutcome::Decoy {
+                        parts: parse_cells(field("the decoy cells")?)?,
+                    },
+                    other => return Err(format!("unknown result '{other}'")),
+                };
+                self.shots.push(RecordedShot {
+                    time,
+                    player,
+                    at,
+                    result,
+                });
+            }
+            "radar" => {
+                let time = number(field("the time")?)?;
+                let player = parse_player(field("the player")?)?;
+                let at = parse_point(field("the cell")?)?;
+                let count = number(field("the count")?)?;
+                self.scans.push(RecordedScan {
+                    time,
+                    player,
+                    at,
+                    count,
+                });
+            }
+            "move" => {
+                let time = number(field("the time")?)?;
+                let player = parse_player(field("the player")?)?;
+                let after = number(field("the shot count")?)?;
+                let moved = match field("the ship") {
+                    Err(_) => None,
+                    Ok(ship) => {
+                        let maneuver = field("the maneuver")?;
+                        let maneuver = Maneuver::ALL
+                            .into_iter()
+                            .find(|m| m.to_string() == maneuver)
+                            .ok_or(format!("unknown maneuver '{maneuver}'"))?;
+                        Some((number(ship)?, maneuver))
+                    }
+                };
+                self.moves.push(RecordedMove {
+                    time,
+                    player,
+                    after,
+                    moved,
+                });
+            }
+            "repair" => {
+                let time = number(field("the time")?)?;
+                let player = parse_player(field("the player")?)?;
+                let after = number(field("the shot count")?)?;
+                let at = parse_point(field("the cell")?)?;
+                self.repairs.push(RecordedRepair {
+                    time,
+                    player,
+                    after,
+                    at,
+                });
+            }
+            "winner" => self.winner = Some(parse_player(field("the winner")?)?),
+            other => return Err(format!("unknown entry '{other}'")),
+        }
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, RecordError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    pub fn write(&self, path: &Path) -> Result<(), RecordError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+    // writes the record into the directory, named after when the game started
+    pub fn write_to(&self, dir: &Path) -> Result<PathBuf, RecordError> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        self.write(&path)?;
+        Ok(path)
+    }
+    pub fn file_name(&self) -> String {
+        format!("{}-{}.txt", self.started, self.seed)
+    }
+    pub fn stats(&self, player: CurrentPlayer) -> PlayerStats {
+        let mut stats = PlayerStats::default();
+        let opponent = self.player(player.other()).layout.as_ref();
+        for shot in self.shots.iter().filter(|shot| shot.player == player) {
+            stats.shots += 1;
+            match &shot.result {
+                ShotOutcome::Miss | ShotOutcome::Mine => {}
+                ShotOutcome::Hit | ShotOutcome::Damaged | ShotOutcome::Decoy { .. } => {
+                    stats.hits += 1
+                }
+                ShotOutcome::Sunk { parts } => {
+                    stats.hits += 1;
+                    // layouts list the ships in the order of the fleet, see PlayerBoard::renumber
+                    let name = opponent
+                        .and_then(|layout| layout.iter().position(|ship| ship.contains(&shot.at)))
+                        .and_then(|id| self.fleet.get(id))
+                        .map_or(format!("{} cell ship", parts.len()), |ship| {
+                            ship.name.clone()
+                        });
+                    stats.sunk.push((name, stats.shots));
+                }
+            }
+        }
+        stats
+    }
+}
+
+// How one player shot during a game.
+#[derive(Default)]
+pub struct PlayerStats {
+    pub shots: usize,
+    pub hits: usize,
+    // the ships they sank, with the turn each one went down on
+    pub sunk: Vec<(String, usize)>,
+}
+impl PlayerStats {
+    // in percent
+    pub fn accuracy(&self) -> f64 {
+        if self.shots == 0 {
+            return 0.0;
+        }
+        self.hits as f64 * 100.0 / self.shots as f64
+    }
+}
+
+// where finished games are recorded
+pub fn default_dir() -> PathBuf {
+    save::data_dir().join("records")
+}
+
+// replays a move on a board being rebuilt, the misses are forgotten like on the real board
+fn move_ship(
+    grid: &mut [Vec<Tile>],
+    ships: &mut [Vec<Point>],
+    moved: Option<(usize, Maneuver)>,
+    blank: Tile,
+) {
+    for tile in grid.iter_mut().flatten() {
+        if *tile == Tile::Miss {
+            *tile = blank;
+        }
+    }
+    let Some((id, maneuver)) = moved else {
+        return;
+    };
+    let Some(parts) = ships.get(id).and_then(|ship| maneuver.apply(ship)) else {
+        return;
+    };
+    if parts
+        .iter()
+        .any(|p| p.y >= grid.len() || p.x >= grid[p.y].len())
+    {
+        return;
+    }
+    for p in &ships[id] {
+        grid[p.y][p.x] = blank;
+    }
+    for p in &parts {
+        grid[p.y][p.x] = Tile::Ship(id as u8);
+    }
+    ships[id] = parts;
+}
+
+fn cells(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<String>>()
+        .join(";")
+}
+fn number<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("'{s}' is not a number"))
+}
+fn parse_point(s: &str) -> Result<Point, String> {
+    let (x, y) = s.split_once(',').ok_or(format!("'{s}' is not a cell"))?;
+    Ok(Point::new(number(x)?, number(y)?))
+}
+fn parse_cells(s: &str) -> Result<Vec<Point>, String> {
+    s.split(';').map(parse_point).collect()
+}
+fn parse_player(s: &str) -> Result<CurrentPlayer, String> {
+    match s {
+        "1" => Ok(CurrentPlayer::First),
+        "2" => Ok(CurrentPlayer::Second),
+        other => Err(format!("'{other}' is not player 1 or 2")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        CurrentPlayer,
+        config::{GameConfig, Map},
+        player_board::PlayerBoard,
+        player_board::ShotOutcome,
+        players::Layout,
+        point::Point,
+        record::{GameRecord, RecordError},
+        rng,
+        ship::{Maneuver, ShipBlueprint},
+        tile::Tile,
+    };
+
+    fn record() -> GameRecord {
+        let mut record = GameRecord::new(&GameConfig::default().with_seed(3));
+        record.started = 1_760_780_000;
+        record.players[0].name = String::from("mistr");
+        record.players[0].layout = Some(vec![vec![Point::new(0, 0), Point::new(0, 1)]]);
+        record.players[1].name = String::from("Clanker (hard)");
+        record.shot(CurrentPlayer::First, Point::new(5, 5), ShotOutcome::Miss);
+        record.shot(CurrentPlayer::Second, Point::new(0, 1), ShotOutcome::Hit);
+        record.shot(CurrentPlayer::First, Point::new(2, 2), ShotOutcome::Hit);
+        record.shot(
+            CurrentPlayer::Second,
+            Point::new(0, 0),
+            ShotOutcome::Sunk {
+                parts: vec![Point::new(0, 0), Point::new(0, 1)],
+            },
+        );
+        record.winner = Some(CurrentPlayer::Second);
+        record
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let mut record = record();
+        record.scan(CurrentPlayer::First, Point::new(1, 1), 2);
+        let text = record.to_text();
+        assert!(text.starts_with("battleships-record 9\n"));
+        assert!(text.contains("player 2 Clanker (hard)\n"));
+        assert!(text.contains(" 2 0,0 sunk 0,0;0,1\n"));
+        assert!(text.contains(" 1 1,1 2\n"));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+    }
+    #[test]
+    fn stats_count_each_players_shots() {
+        let mut record = record();
+        record.fleet.truncate(1);
+        let first = record.stats(CurrentPlayer::First);
+        assert_eq!((first.shots, first.hits), (2, 1));
+        assert_eq!(first.accuracy(), 50.0);
+        assert!(first.sunk.is_empty());
+        let second = record.stats(CurrentPlayer::Second);
+        assert_eq!((second.shots, second.hits), (2, 2));
+        assert_eq!(second.sunk, vec![(record.fleet[0].name.clone(), 2)]);
+    }
+    #[test]
+    fn sunk_ships_are_named_after_their_blueprint() {
+        // the default fleet isn't listed biggest first, the way ships are placed
+        let config = GameConfig::default().with_seed(3);
+        let mut board = Layout::Random
+            .place(
+                &PlayerBoard::new(config.rules.width, config.rules.height),
+                &config.fleet,
+                &mut rng::from_seed(3),
+            )
+            .expect("the default fleet fits");
+        let mut record = GameRecord::new(&config);
+        record.players[1].layout = Some(board.fleet());
+        for p in board.fleet().into_iter().flatten() {
+            let result =
+                ShotOutcome::from(&board.process_shot(p).expect("every part is shot once"));
+            record.shot(CurrentPlayer::First, p, result);
+        }
+        let sunk = record.stats(CurrentPlayer::First).sunk;
+        assert_eq!(sunk.len(), config.fleet.len());
+        for (parts, (name, _)) in record
+            .shots
+            .iter()
+            .filter_map(|shot| match &shot.result {
+                ShotOutcome::Sunk { parts } => Some(parts),
+                _ => None,
+            })
+            .zip(&sunk)
+        {
+            let blueprint = config
+                .fleet
+                .iter()
+                .find(|ship| ship.name == *name)
+                .expect("every ship is named after its blueprint");
+            assert_eq!(blueprint.parts.len(), parts.len());
+        }
+    }
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let text = "battleships-record 1\n# a comment\n\nboard 4 4\nshot 0 1 1,1 miss\n";
+        let record = GameRecord::parse(text).expect("should parse");
+        assert_eq!(record.shots.len(), 1);
+    }
+    #[test]
+    fn bad_records_are_refused() {
+        assert!(matches!(
+            GameRecord::parse("battleships-record 99\n"),
+            Err(RecordError::Version(99))
+        ));
+        assert!(matches!(
+            GameRecord::parse("battleships-record 1\nboard 4 4\nshot 0 3 1,1 miss\n"),
+            Err(RecordError::Line(3, _))
+        ));
+        assert!(GameRecord::parse("battleships-record 1\nboard 4 4\nshot 0 1 9,9 miss\n").is_err());
+        assert!(GameRecord::parse("chess 1\n").is_err());
+    }
+    #[test]
+    fn boards_replay_step_by_step() {
+        let record = record();
+        let board = |step| record.board_at(CurrentPlayer::First, step);
+        assert_eq!(board(0)[1][0], Tile::Ship(0));
+        assert_eq!(board(2)[1][0], Tile::Hit);
+        assert_eq!(board(4)[0][0], Tile::SunkenShip);
+        assert_eq!(board(4)[1][0], Tile::SunkenShip);
+        // player 2 never revealed their ships
+        let other = record.board_at(CurrentPlayer::Second, 4);
+        assert_eq!(other[5][5], Tile::Miss);
+        assert_eq!(other[2][2], Tile::Hit);
+        assert_eq!(other[0][0], Tile::Hidden);
+    }
+    #[test]
+    fn maps_are_recorded() {
+        let mut record = record();
+        record.map = Map {
+            islands: vec![Point::new(4, 4), Point::new(4, 5)],
+            mines: vec![Point::new(7, 7)],
+        };
+        record.shot(CurrentPlayer::Second, Point::new(7, 7), ShotOutcome::Mine);
+        let text = record.to_text();
+        assert!(text.contains("\nisland 4,4;4,5\nmine 7,7\n"));
+        assert!(text.contains(" 2 7,7 mine\n"));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+
+        let board = record.board_at(CurrentPlayer::First, 0);
+        assert_eq!(board[4][4], Tile::Island);
+        assert_eq!(board[7][7], Tile::Mine);
+        assert_eq!(
+            record.board_at(CurrentPlayer::First, 5)[7][7],
+            Tile::Exploded
+        );
+        // the other player's mines stay hidden, like their ships
+        let other = record.board_at(CurrentPlayer::Second, 0);
+        assert_eq!((other[5][4], other[7][7]), (Tile::Island, Tile::Hidden));
+    }
+    #[test]
+    fn moves_are_recorded_and_replayed() {
+        let mut record = GameRecord::new(&GameConfig::default().with_seed(3));
+        record.players[0].layout = Some(vec![vec![Point::new(0, 0), Point::new(0, 1)]]);
+        record.shot(CurrentPlayer::Second, Point::new(5, 5), ShotOutcome::Miss);
+        record.moved(CurrentPlayer::First, Some((0, Maneuver::Right)));
+        record.moved(CurrentPlayer::Second, None);
+        let text = record.to_text();
+        assert!(text.contains(
+            " 1 1 0 right
+"
+        ));
+        assert!(text.contains(
+            " 2 1
+"
+        ));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+
+        let before = record.board_at(CurrentPlayer::First, 0);
+        assert_eq!(before[0][0], Tile::Ship(0));
+        // the move came right after the first shot, the miss is forgotten with it
+        let after = record.board_at(CurrentPlayer::First, 1);
+        assert_eq!((after[0][0], after[0][1]), (Tile::Empty, Tile::Ship(0)));
+        assert_eq!(after[5][5], Tile::Empty);
+
+        record.reveal_moves(CurrentPlayer::Second, &[(0, Maneuver::Rotate)]);
+        assert_eq!(record.moves[1].moved, Some((0, Maneuver::Rotate)));
+    }
+    #[test]
+    fn repairs_are_recorded_and_replayed() {
+        let mut record = record();
+        // the second player's ship is hidden, the repair shows there is a ship part again
+        record.repaired(CurrentPlayer::Second, Point::new(2, 2));
+        let text = record.to_text();
+        assert!(text.contains(" 2 4 2,2\n"));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+        let other = record.board_at(CurrentPlayer::Second, 3);
+        assert_eq!(other[2][2], Tile::Hit);
+        assert_eq!(
+            record.board_at(CurrentPlayer::Second, 4)[2][2],
+            Tile::Repaired
+        );
+    }
+    #[test]
+    fn blueprints_round_trip() {
+        let mut record = record();
+        record.fleet = vec![
+            ShipBlueprint::new(
+                vec![Point::new(0, 0), Point::new(0, 1)],
+                String::from("plain"),
+            ),
+            ShipBlueprint::new(
+                vec![Point::new(0, 0), Point::new(1, 0)],
+                String::from("armored one"),
+            )
+            .with_hp(vec![2, 1]),
+            ShipBlueprint::new(vec![Point::new(0, 0)], String::from("bluff")).with_decoy(true),
+        ];
+        let text = record.to_text();
+        assert!(text.contains("\nblueprint 0,0;0,1 - ship plain\n"));
+        assert!(text.contains("\nblueprint 0,0;1,0 2,1 ship armored one\n"));
+        assert!(text.contains("\nblueprint 0,0 - decoy bluff\n"));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+        // older records have no hit points or decoys on their blueprints
+        let old = GameRecord::parse("battleships-record 7\nboard 4 4\nblueprint 0,0;0,1 new one\n")
+            .expect("should parse");
+        assert_eq!(old.fleet[0].name, "new one");
+        assert!(old.fleet[0].hp.is_empty());
+        let old = GameRecord::parse("battleships-record 8\nboard 4 4\nblueprint 0,0 2 decoy\n")
+            .expect("should parse");
+        assert_eq!(
+            (old.fleet[0].name.as_str(), old.fleet[0].decoy),
+            ("decoy", false)
+        );
+        assert!(
+            GameRecord::parse("battleships-record 9\nboard 4 4\nblueprint 0,0;0,1 2 ship short\n")
+                .is_err()
+        );
+        assert!(
+            GameRecord::parse("battleships-record 9\nboard 4 4\nblueprint 0,0 - boat x\n").is_err()
+        );
+    }
+    #[test]
+    fn bluff_fleet_round_trips() {
+        let config = GameConfig::from_toml(include_str!("../../fleets/bluff.toml"))
+            .expect("the bluff fleet should load");
+        let record = GameRecord::new(&config);
+        assert!(record.fleet.iter().any(|ship| ship.decoy));
+        let parsed = GameRecord::parse(&record.to_text()).expect("should parse");
+        assert_eq!(parsed.fleet, config.fleet);
+    }
+    #[test]
+    fn decoys_are_recorded_and_replayed() {
+        let mut record = record();
+        let decoy = vec![Point::new(7, 7), Point::new(8, 7)];
+        record.shot(
+            CurrentPlayer::First,
+            Point::new(8, 7),
+            ShotOutcome::Decoy {
+                parts: decoy.clone(),
+            },
+        );
+        let text = record.to_text();
+        assert!(text.contains(" 1 8,7 decoy 7,7;8,7\n"));
+        assert_eq!(GameRecord::parse(&text).expect("should parse"), record);
+        let board = record.board_at(CurrentPlayer::Second, 5);
+        assert!(decoy.iter().all(|p| board[p.y][p.x] == Tile::Decoy));
+        assert_eq!(record.stats(CurrentPlayer::First).hits, 2);
+        assert!(record.stats(CurrentPlayer::First).sunk.is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+// Every random decision in a game is drawn from one of these, all of them derived from the
+// seed in the GameConfig, so the same seed and the same inputs replay the same game.
+pub type GameRng = StdRng;
+
+pub fn from_seed(seed: u64) -> GameRng {
+    GameRng::seed_from_u64(seed)
+}
+pub fn random_seed() -> u64 {
+    rand::random()
+}
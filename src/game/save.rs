@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    CurrentPlayer, GameEnd, GameMode, PlayerVsPlayerMode, SinglePlayer,
+    config::GameConfig,
+    player_board::{PlayerBoard, ViewBoard},
+    players::{Computer, Difficulty, Layout, Player},
+    point::Point,
+    record::GameRecord,
+    weapon::Arsenal,
+};
+
+// Bump this whenever the save format changes, older saves are refused instead of misread.
+const SAVE_VERSION: u32 = 3;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Version(u32),
+}
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not access the save: {err}"),
+            Self::Parse(err) => write!(f, "the save is damaged: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "the save has format version {version}, this game reads {SAVE_VERSION}"
+            ),
+        }
+    }
+}
+impl Error for SaveError {}
+impl From<std::io::Error> for SaveError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+// A game in progress, everything both players know and whose turn it is.
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    version: u32,
+    config: GameConfig,
+    seed: u64,
+    current_player: CurrentPlayer,
+    record: GameRecord,
+    player1: SavedPlayer,
+    player2: SavedPlayer,
+    // special attack charges left, by player
+    arsenals: [Arsenal; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SavedPlayer {
+    Human(HumanState),
+    Computer(ComputerState),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HumanState {
+    pub board: PlayerBoard,
+    pub opponent_board: ViewBoard,
+    pub last_cursor_pos: Option<Point>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ComputerState {
+    pub board: PlayerBoard,
+    pub opponent_board: ViewBoard,
+    pub difficulty: Difficulty,
+    pub layout: Layout,
+    pub strategy: serde_json::Value,
+    // the computer's rng starts over from this when the game is continued
+    pub seed: u64,
+    pub name: String,
+}
+
+impl SavedGame {
+    pub fn new(
+        config: GameConfig,
+        current_player: CurrentPlayer,
+        record: GameRecord,
+        player1: SavedPlayer,
+        player2: SavedPlayer,
+    ) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            seed: config.seed,
+            config,
+            current_player,
+            record,
+            player1,
+            player2,
+            arsenals: Default::default(),
+        }
+    }
+    pub fn with_arsenals(mut self, arsenals: [Arsenal; 2]) -> Self {
+        self.arsenals = arsenals;
+        self
+    }
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let saved: Self =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(SaveError::Parse)?;
+        if saved.version != SAVE_VERSION {
+            return Err(SaveError::Version(saved.version));
+        }
+        Ok(saved)
+    }
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(self).map_err(SaveError::Parse)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+    // continues the game, two humans play it hotseat
+    pub fn resume(self, term: Rc<RefCell<ratatui::DefaultTerminal>>) -> Result<GameEnd, SaveError> {
+        let config = self.config.with_seed(self.seed);
+        let current = self.current_player;
+        let record = self.record;
+        let arsenals = self.arsenals;
+        let end = match (self.player1, self.player2) {
+            (SavedPlayer::Human(p1), SavedPlayer::Human(p2)) => {
+                let p1 = Player::restore(Rc::clone(&term), p1);
+                let p2 = Player::restore(Rc::clone(&term), p2);
+                let game =
+                    SinglePlayer::resume(p1, p2, config, current, record).with_arsenals(arsenals);
+                PlayerVsPlayerMode::resume(game, term).run()
+            }
+            (SavedPlayer::Human(p1), SavedPlayer::Computer(p2)) => {
+                let p1 = Player::restore(term, p1);
+                SinglePlayer::resume(p1, Computer::restore(p2)?, config, current, record)
+                    .with_arsenals(arsenals)
+                    .run()
+            }
+            (SavedPlayer::Computer(p1), SavedPlayer::Human(p2)) => {
+                let p2 = Player::restore(term, p2);
+                SinglePlayer::resume(Computer::restore(p1)?, p2, config, current, record)
+                    .with_arsenals(arsenals)
+                    .run()
+            }
+            (SavedPlayer::Computer(p1), SavedPlayer::Computer(p2)) => {
+                let (p1, p2) = (Computer::restore(p1)?, Computer::restore(p2)?);
+                SinglePlayer::resume(p1, p2, config, current, record)
+                    .with_arsenals(arsenals)
+                    .run()
+            }
+        };
+        Ok(end)
+    }
+}
+
+// where the game keeps its files, $XDG_DATA_HOME/battleships or ~/.local/share/battleships
+pub fn data_dir() -> PathBuf {
+    let data = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    match data {
+        Some(dir) => dir.join("battleships"),
+        None => PathBuf::from("battleships"),
+    }
+}
+// where the game in progress is kept
+pub fn default_path() -> PathBuf {
+    data_dir().join("save.json")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        CurrentPlayer, Setup,
+        config::GameConfig,
+        player_board::{PlayerBoard, ShotResult},
+        players::{Computer, Difficulty, GamePlayer},
+        point::Point,
+        record::GameRecord,
+        rotation::Rotation,
+        save::{SAVE_VERSION, SaveError, SavedGame},
+    };
+
+    fn saved_game() -> SavedGame {
+        let config = GameConfig::default().with_seed(9);
+        let mut p1 = Computer::new(Difficulty::Easy);
+        let mut p2 = Computer::new(Difficulty::Brutal);
+        p1.setup(config.clone());
+        p2.setup(config.clone());
+        SavedGame::new(
+            config.clone(),
+            CurrentPlayer::Second,
+            GameRecord::new(&config),
+            p1.save().expect("computers can be saved"),
+            p2.save().expect("computers can be saved"),
+        )
+    }
+
+    #[test]
+    fn save_file_round_trips() {
+        let path = std::env::temp_dir().join(format!("battleships-{}.json", std::process::id()));
+        saved_game().write(&path).expect("should write");
+        let loaded = SavedGame::read(&path);
+        std::fs::remove_file(&path).expect("should clean up");
+        let loaded = loaded.expect("should read");
+        assert_eq!(loaded.seed, 9);
+        assert!(matches!(loaded.current_player, CurrentPlayer::Second));
+        assert_eq!(loaded.config.fleet, GameConfig::default().fleet);
+    }
+    #[test]
+    fn other_versions_are_refused() {
+        let mut saved = saved_game();
+        saved.version = SAVE_VERSION + 1;
+        let path = std::env::temp_dir().join(format!("battleships-v-{}.json", std::process::id()));
+        saved.write(&path).expect("should write");
+        let loaded = SavedGame::read(&path);
+        std::fs::remove_file(&path).expect("should clean up");
+        assert!(matches!(loaded, Err(SaveError::Version(_))));
+    }
+    #[test]
+    fn hits_survive_a_save() {
+        let mut board = PlayerBoard::new(10, 10);
+        let ship = GameConfig::default().fleet[0].clone();
+        board
+            .place_ship(&ship, Point::new(0, 0), Rotation::None)
+            .expect("should place");
+        let parts = board.fleet()[0].clone();
+        for p in &parts[1..] {
+            board.process_shot(*p).expect("should hit");
+        }
+        let json = serde_json::to_string(&board).expect("should serialize");
+        let mut board: PlayerBoard = serde_json::from_str(&json).expect("should deserialize");
+        assert!(matches!(
+            board.process_shot(parts[0]),
+            Ok(ShotResult::ShipSunk(_))
+        ));
+        assert!(board.process_shot(parts[1]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+use ratatui::text::{Line, Text};
+
+use crate::game::{
+    CurrentPlayer, GameEnd, GameMode, Setup,
+    config::{FirstPlayer, GameConfig},
+    record::GameRecord,
+    rng::{self, GameRng},
+    ui::WaitForKey,
+};
+
+// Where a series stands, handed out with the result of every game in it.
+#[derive(Clone)]
+pub struct SeriesScore {
+    pub best_of: usize,
+    // games won by player 1 and player 2
+    pub wins: [usize; 2],
+    // every game played before this one, the newest last
+    pub earlier: Vec<GameRecord>,
+}
+impl SeriesScore {
+    pub fn wins(&self, player: CurrentPlayer) -> usize {
+        self.wins[player as usize - 1]
+    }
+    // the series is decided once a player has won more than half of it
+    pub fn winner(&self) -> Option<CurrentPlayer> {
+        [CurrentPlayer::First, CurrentPlayer::Second]
+            .into_iter()
+            .find(|&player| self.wins(player) * 2 > self.best_of)
+    }
+}
+
+// Plays the same two players against each other until one of them has won the best of N games.
+// Every game is set up again with a fresh seed and the other player shooting first.
+pub struct Series<M> {
+    game: M,
+    score: SeriesScore,
+    config: GameConfig,
+    rng: GameRng,
+    first: CurrentPlayer,
+    // the standings are shown between games when there is a screen to show them on
+    term: Option<Rc<RefCell<ratatui::DefaultTerminal>>>,
+}
+
+impl<M> Series<M>
+where
+    M: GameMode + Setup<GameConfig>,
+{
+    // an even N could end in a tie, it's rounded up to the next odd number
+    pub fn new(game: M, best_of: usize) -> Self {
+        Self {
+            game,
+            score: SeriesScore {
+                best_of: best_of.max(1) | 1,
+                wins: [0, 0],
+                earlier: Vec::new(),
+            },
+            config: GameConfig::default(),
+            rng: rng::from_seed(0),
+            first: CurrentPlayer::First,
+            term: None,
+        }
+    }
+    pub fn with_terminal(mut self, term: Rc<RefCell<ratatui::DefaultTerminal>>) -> Self {
+        self.term = Some(term);
+        self
+    }
+    // the next game gets its own seed and the other player shoots first
+    fn next_game(&mut self) {
+        self.first = self.first.other();
+        let mut config = self.config.clone().with_seed(self.rng.random());
+        config.rules.first_player = match self.first {
+            CurrentPlayer::First => FirstPlayer::First,
+            CurrentPlayer::Second => FirstPlayer::Second,
+        };
+        self.game.setup(config);
+    }
+    fn show_standings(&self, record: &GameRecord) {
+        let Some(term) = &self.term else {
+            return;
+        };
+        let name = |player| &record.player(player).name;
+        let wait = WaitForKey::new(Text::from(vec![
+            Line::from(format!(
+                "Game {} of a best of {} is over",
+                self.score.earlier.len(),
+                self.score.best_of
+            )),
+            Line::from(""),
+            Line::from(format!(
+                "{} {} - {} {}",
+                name(CurrentPlayer::First),
+                self.score.wins(CurrentPlayer::First),
+                self.score.wins(CurrentPlayer::Second),
+                name(CurrentPlayer::Second),
+            )),
+            Line::from(""),
+            Line::from("press any key to start the next game"),
+        ]));
+        let _ = term.borrow_mut().draw(|f| wait.render(f, f.area()));
+        wait.wait(None);
+    }
+}
+
+impl<M> GameMode for Series<M>
+where
+    M: GameMode + Setup<GameConfig>,
+{
+    fn play(&mut self) -> GameEnd {
+        loop {
+            let mut result = match self.game.play() {
+                GameEnd::Over(result) => result,
+                quit => return quit,
+            };
+            // a draw counts for neither player
+            if let Some(winner) = result.winner {
+                self.score.wins[winner as usize - 1] += 1;
+            }
+            // a cheater doesn't get to play on, and nobody is left to play with one who left
+            if self.score.winner().is_some()
+                || result.tampered.is_some()
+                || result.forfeit.is_some()
+            {
+                result.series = Some(self.score.clone());
+                return GameEnd::Over(result);
+            }
+            self.score.earlier.push(result.record);
+            self.show_standings(self.score.earlier.last().expect("a game was just played"));
+            self.next_game();
+        }
+    }
+}
+
+impl<M> Setup<GameConfig> for Series<M>
+where
+    M: GameMode + Setup<GameConfig>,
+{
+    fn setup(&mut self, config: GameConfig) {
+        // the first game is played as configured, the rest are drawn from its seed
+        let mut rng = rng::from_seed(config.seed);
+        self.first = match config.rules.first_player {
+            FirstPlayer::First => CurrentPlayer::First,
+            FirstPlayer::Second => CurrentPlayer::Second,
+            FirstPlayer::Random if rng.random_bool(0.5) => CurrentPlayer::First,
+            FirstPlayer::Random => CurrentPlayer::Second,
+        };
+        let mut first = config.clone();
+        first.rules.first_player = match self.first {
+            CurrentPlayer::First => FirstPlayer::First,
+            CurrentPlayer::Second => FirstPlayer::Second,
+        };
+        self.rng = rng;
+        self.config = config;
+        self.score.wins = [0, 0];
+        self.score.earlier.clear();
+        self.game.setup(first);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        CurrentPlayer, GameEnd, GameMode, Setup, SinglePlayer,
+        config::GameConfig,
+        players::{Computer, Difficulty},
+        series::Series,
+    };
+
+    #[test]
+    fn series_ends_once_a_player_has_won_most_games() {
+        let game = SinglePlayer::new(
+            Computer::new(Difficulty::Easy),
+            Computer::new(Difficulty::Easy),
+        );
+        let mut series = Series::new(game, 4);
+        series.setup(GameConfig::default().with_seed(11));
+        let GameEnd::Over(result) = series.run() else {
+            panic!("computers don't quit");
+        };
+        let score = result.series.expect("the result should carry the score");
+        assert_eq!(score.best_of, 5);
+        assert_eq!(score.winner(), result.winner);
+        assert_eq!(score.wins(result.winner.expect("the last game was won")), 3);
+        assert_eq!(score.earlier.len() + 1, score.wins.iter().sum::<usize>());
+        // who shot first alternates from game to game
+        let firsts: Vec<CurrentPlayer> = score
+            .earlier
+            .iter()
+            .chain([&result.record])
+            .map(|record| record.shots[0].player)
+            .collect();
+        assert!(firsts.windows(2).all(|pair| pair[0] != pair[1]));
+        // and every game was set up with its own seed
+        assert!(
+            score
+                .earlier
+                .iter()
+                .all(|record| record.seed != result.record.seed)
+        );
+    }
+}
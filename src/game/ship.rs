@@ -1,46 +1,169 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
 use crate::game::point::Point;
 use crate::game::rotation::Rotation;
-use std::sync::atomic::AtomicU8;
 
-static NEXT_SHIP_ID: AtomicU8 = AtomicU8::new(0);
+// What a hit did to a ship.
+pub enum Damage<'a> {
+    // the armor held, the part takes more hits
+    Dented,
+    Destroyed,
+    // the last part went down, with every part of the ship
+    Sunk(&'a Vec<Point>),
+}
+
+// How an undamaged ship can move instead of its player firing, under the moving ships rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Maneuver {
+    Up,
+    Down,
+    Left,
+    Right,
+    // a quarter turn in place, the ship keeps its upper left corner
+    Rotate,
+}
+impl Maneuver {
+    pub const ALL: [Self; 5] = [Self::Up, Self::Down, Self::Left, Self::Right, Self::Rotate];
+    // where the parts end up, in the same order, None when a part would leave the top or the
+    // left edge, the other edges are up to the board
+    pub fn apply(self, parts: &[Point]) -> Option<Vec<Point>> {
+        let step = |dx, dy| parts.iter().map(|p| p.offset(dx, dy)).collect();
+        match self {
+            Self::Up => step(0, -1),
+            Self::Down => step(0, 1),
+            Self::Left => step(-1, 0),
+            Self::Right => step(1, 0),
+            Self::Rotate => {
+                let min_x = parts.iter().map(|p| p.x).min()?;
+                let min_y = parts.iter().map(|p| p.y).min()?;
+                let max_y = parts.iter().map(|p| p.y).max()?;
+                // turned the same way as ShipBlueprint::rotate, then moved back to the corner
+                Some(
+                    parts
+                        .iter()
+                        .map(|p| Point::new(min_x + max_y - p.y, min_y + p.x - min_x))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+impl Display for Maneuver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Rotate => "rotate",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ship {
     pub id: u8,
+    #[allow(dead_code)]
     pub name: String,
-    parts_alive: usize,
+    // the hits every part can still take, in the order of the parts
+    hp: Vec<u8>,
     pub parts: Vec<Point>,
+    // the parts that were destroyed and patched up again, each one can only be repaired once
+    #[serde(default)]
+    pub repaired: Vec<Point>,
+    // doesn't count toward losing the game
+    #[serde(default)]
+    pub decoy: bool,
 }
 impl Ship {
-    pub fn new(points: Vec<Point>, name: String) -> Self {
-        let id = NEXT_SHIP_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-        if id == u8::MAX {
-            panic!("out of SHIP_IDs");
-        }
+    pub fn new(id: u8, points: Vec<Point>, name: String) -> Self {
         Self {
             id,
-            parts_alive: points.len(),
+            hp: vec![1; points.len()],
             parts: points,
             name,
+            repaired: Vec::new(),
+            decoy: false,
         }
     }
-    pub fn hit(&mut self) -> Option<&Vec<Point>> {
-        self.parts_alive -= 1;
-        if self.parts_alive > 0 {
-            return None;
+    pub fn with_hp(mut self, hp: Vec<u8>) -> Self {
+        self.hp = hp;
+        self
+    }
+    pub fn with_decoy(mut self, decoy: bool) -> Self {
+        self.decoy = decoy;
+        self
+    }
+    pub fn hit(&mut self, p: Point) -> Damage<'_> {
+        let i = self
+            .parts
+            .iter()
+            .position(|part| *part == p)
+            .expect("the shot is on the ship");
+        self.hp[i] = self.hp[i].saturating_sub(1);
+        if self.hp[i] > 0 {
+            return Damage::Dented;
         }
-        Some(&self.parts)
+        if self.is_alive() {
+            return Damage::Destroyed;
+        }
+        Damage::Sunk(&self.parts)
     }
     pub fn is_alive(&self) -> bool {
-        self.parts_alive > 0
+        self.hp.iter().any(|hp| *hp > 0)
+    }
+    // moved to new cells, the hit points go along with the parts
+    pub fn move_to(&mut self, parts: Vec<Point>) {
+        self.parts = parts;
+    }
+    // a destroyed part takes one more hit again
+    pub fn repair(&mut self, p: Point) {
+        let i = self
+            .parts
+            .iter()
+            .position(|part| *part == p)
+            .expect("the repair is on the ship");
+        self.hp[i] = 1;
+        self.repaired.push(p);
     }
 }
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ShipBlueprint {
     pub parts: Vec<Point>,
     pub name: String,
+    // how many hits every part takes, in the order of the parts, empty when it's one each
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hp: Vec<u8>,
+    // a bluff, it is shot at like a ship but the game doesn't need it sunk
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub decoy: bool,
 }
 impl ShipBlueprint {
     pub fn new(parts: Vec<Point>, name: String) -> Self {
-        Self { parts, name }
+        Self {
+            parts,
+            name,
+            hp: Vec::new(),
+            decoy: false,
+        }
+    }
+    pub fn with_hp(mut self, hp: Vec<u8>) -> Self {
+        self.hp = hp;
+        self
+    }
+    pub fn with_decoy(mut self, decoy: bool) -> Self {
+        self.decoy = decoy;
+        self
+    }
+    // the hits every part takes
+    pub fn hp(&self) -> Vec<u8> {
+        if self.hp.is_empty() {
+            return vec![1; self.parts.len()];
+        }
+        self.hp.clone()
     }
     pub fn rotate(&self, target: Rotation) -> Vec<Point> {
         if self.parts.is_empty() {
@@ -93,4 +216,30 @@ impl ShipBlueprint {
             })
             .collect()
     }
+    // every distinct shape the ship can take, moved to the origin and sorted
+    pub fn rotations(&self) -> Vec<Vec<Point>> {
+        let mut shapes: Vec<Vec<Point>> = Vec::new();
+        let mut rotation = Rotation::None;
+        loop {
+            let shape = normalize(&self.rotate(rotation));
+            if !shapes.contains(&shape) {
+                shapes.push(shape);
+            }
+            rotation = rotation.next();
+            if rotation == Rotation::None {
+                break shapes;
+            }
+        }
+    }
+}
+// moves the points so they touch both axes and sorts them, so equal shapes compare equal
+pub fn normalize(points: &[Point]) -> Vec<Point> {
+    let min_x = points.iter().map(|p| p.x).min().unwrap_or(0);
+    let min_y = points.iter().map(|p| p.y).min().unwrap_or(0);
+    let mut shape: Vec<Point> = points
+        .iter()
+        .map(|p| Point::new(p.x - min_x, p.y - min_y))
+        .collect();
+    shape.sort();
+    shape
 }
@@ -1,27 +1,71 @@
-use crossterm::style::Stylize;
 use ratatui::{
     style::{Color, Style},
     text::Text,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum Tile {
     Ship(u8),
     SunkenShip,
     Hit,
+    // an armored part that was hit but still holds
+    Damaged,
     Miss,
     Empty,
     Hidden,
+    // a hidden cell a radar swept over
+    Scanned,
+    // the centre of a radar sweep, with how many ship cells it found
+    Pinged(u8),
+    // land, both players see it
+    Island,
+    // a mine that hasn't gone off, only its owner sees it
+    Mine,
+    // a mine that was shot and went off
+    Exploded,
+    // a miss or a radar sweep from before the opponent moved a ship, it may be out of date
+    Stale,
+    // a hit the opponent repaired, a ship part is there and can be shot again
+    Repaired,
+    // a part of a decoy that was destroyed, it never was a real ship
+    Decoy,
 }
 impl Tile {
-    pub fn to_styled(&self) -> Text {
-        match *self {
+    // nothing was shot there yet, radar sweeps don't count
+    pub fn is_unknown(self) -> bool {
+        matches!(
+            self,
+            Self::Hidden | Self::Scanned | Self::Pinged(_) | Self::Stale
+        )
+    }
+    // a shot there can still do something
+    pub fn is_target(self) -> bool {
+        self.is_unknown() || matches!(self, Self::Damaged | Self::Repaired)
+    }
+    pub fn to_styled(self) -> Text<'static> {
+        match self {
             Self::Ship(_) => Text::from(" ").style(Style::new().bg(Color::Gray)), // grey
             Self::SunkenShip => Text::from(" ").style(Style::new().bg(Color::Red)), // dark_red
             Self::Hit => Text::from(" ").style(Style::new().bg(Color::LightRed)), // red()
+            Self::Damaged => {
+                Text::from("/").style(Style::new().fg(Color::Black).bg(Color::LightYellow))
+            }
             Self::Miss => Text::from("*").style(Style::new().fg(Color::DarkGray)), // dark_grey()
-            Self::Empty => Text::from(" ").style(Style::new().bg(Color::Blue)),   // dark_blue()
+            Self::Empty => Text::from(" ").style(Style::new().bg(Color::Blue)),    // dark_blue()
             Self::Hidden => Text::from("?").style(Style::new().fg(Color::Gray).bg(Color::Blue)), // blue()
+            Self::Scanned => Text::from("?").style(Style::new().fg(Color::Gray).bg(Color::Cyan)),
+            Self::Pinged(count) => {
+                Text::from(count.to_string()).style(Style::new().fg(Color::Black).bg(Color::Cyan))
+            }
+            Self::Island => Text::from("^").style(Style::new().fg(Color::Black).bg(Color::Green)),
+            Self::Mine => Text::from("o").style(Style::new().fg(Color::Red).bg(Color::Blue)),
+            Self::Exploded => Text::from("X").style(Style::new().fg(Color::Yellow).bg(Color::Red)),
+            Self::Stale => Text::from("~").style(Style::new().fg(Color::DarkGray).bg(Color::Blue)),
+            Self::Decoy => Text::from("d").style(Style::new().fg(Color::Black).bg(Color::Magenta)),
+            Self::Repaired => {
+                Text::from("+").style(Style::new().fg(Color::Black).bg(Color::LightGreen))
+            }
         }
     }
 }
@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use crossterm::event::{self, Event};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Text},
+};
+
+use crate::game::{
+    CurrentPlayer, GameResult,
+    player_board::board_view::BoardView,
+    record::PlayerStats,
+    ui::menu::{Menu, MenuEvent, TextInput},
+};
+
+const STATS_WIDTH: u16 = 30;
+
+// What to do once the game over screen is left.
+pub enum AfterGame {
+    Rematch,
+    Menu,
+    Quit,
+}
+
+enum Entry {
+    Rematch,
+    SaveReplay,
+    Menu,
+    Quit,
+}
+
+// Shows who won, both fleets uncovered and how well each player shot.
+pub struct GameOverScreen {
+    result: GameResult,
+    // a line under the winner, where the record went for example
+    status: String,
+    entries: Vec<Entry>,
+    menu: Menu,
+}
+
+impl GameOverScreen {
+    // network and continued games can't be played again, rematch is left out for them
+    pub fn new(result: GameResult, status: String, rematch: bool) -> Self {
+        let mut entries = vec![Entry::SaveReplay, Entry::Menu, Entry::Quit];
+        if rematch {
+            entries.insert(0, Entry::Rematch);
+        }
+        let labels = entries
+            .iter()
+            .map(|entry| {
+                String::from(match entry {
+                    Entry::Rematch => "Rematch",
+                    Entry::SaveReplay => "Save replay",
+                    Entry::Menu => "Main menu",
+                    Entry::Quit => "Quit",
+                })
+            })
+            .collect();
+        Self {
+            result,
+            status,
+            entries,
+            menu: Menu::new("Game over", labels),
+        }
+    }
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> AfterGame {
+        loop {
+            let _ = terminal.draw(|f| self.render(f, f.area()));
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            let entry = match self.menu.handle_key(key) {
+                Some(MenuEvent::Select(i)) => &self.entries[i],
+                Some(MenuEvent::Back) => return AfterGame::Menu,
+                _ => continue,
+            };
+            match entry {
+                Entry::Rematch => return AfterGame::Rematch,
+                Entry::SaveReplay => self.save_replay(terminal),
+                Entry::Menu => return AfterGame::Menu,
+                Entry::Quit => return AfterGame::Quit,
+            }
+        }
+    }
+    fn save_replay(&mut self, terminal: &mut DefaultTerminal) {
+        let record = &self.result.record;
+        let input = TextInput::new("Save the replay to", &record.file_name(), 60);
+        let Some(path) = input.run(terminal).map(PathBuf::from) else {
+            return;
+        };
+        self.status = match record.write(&path) {
+            Ok(()) => format!("Replay saved to {}", path.display()),
+            Err(err) => format!("{}: {err}", path.display()),
+        };
+    }
+    fn stats_text(&self, player: CurrentPlayer) -> Text<'_> {
+        let stats: PlayerStats = self.result.record.stats(player);
+        let mut lines = vec![
+            Line::from(format!("Shots: {}", stats.shots)),
+            Line::from(format!("Hits: {} ({:.0}%)", stats.hits, stats.accuracy())),
+        ];
+        lines.extend(
+            stats
+                .sunk
+                .into_iter()
+                .map(|(name, turn)| Line::from(format!("Sank {name} on turn {turn}"))),
+        );
+        Text::from(lines).centered()
+    }
+    pub fn render(&self, f: &mut Frame, rect: Rect) {
+        let record = &self.result.record;
+        let (width, height) = (record.width as u16 + 2, record.height as u16 + 2);
+        let stats_height = record.fleet.len() as u16 + 2;
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Length(height),
+                Constraint::Length(1),
+                Constraint::Length(stats_height),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+        let columns = |width: u16, area: Rect| {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(width),
+                    Constraint::Length(2),
+                    Constraint::Length(width),
+                    Constraint::Min(0),
+                ])
+                .split(area)
+        };
+        let stats_width = width.max(STATS_WIDTH);
+
+        let outcome = match (self.result.winner, &self.result.winner_name) {
+            (Some(winner), Some(name)) => {
+                format!("Player {}: {name} has won the game!", winner as i32)
+            }
+            _ => String::from("It's a draw, both fleets went down in the same round!"),
+        };
+        let mut header = vec![Line::from(outcome).style(Style::new().fg(Color::Yellow))];
+        if let Some(score) = &self.result.series
+            && score.best_of > 1
+        {
+            let name = |player| &record.player(player).name;
+            let standing = format!(
+                "{} {} - {} {}",
+                name(CurrentPlayer::First),
+                score.wins(CurrentPlayer::First),
+                score.wins(CurrentPlayer::Second),
+                name(CurrentPlayer::Second),
+            );
+            header.push(Line::from(match score.winner() {
+                Some(winner) => format!(
+                    "{} wins the best of {}, {standing}",
+                    name(winner),
+                    score.best_of
+                ),
+                None => format!("The best of {} was cut short, {standing}", score.best_of),
+            }));
+        }
+        if let Some((name, why)) = &self.result.forfeit {
+            header.push(
+                Line::from(format!("{name} left the game, {why}"))
+                    .style(Style::new().fg(Color::Red)),
+            );
+        }
+        if let Some((name, err)) = &self.result.tampered {
+            header.push(
+                Line::from(format!("Warning: {name} cheated, {err}"))
+                    .style(Style::new().fg(Color::Red)),
+            );
+        }
+        header.push(Line::from(self.status.as_str()));
+        f.render_widget(Text::from(header).centered(), rows[0]);
+
+        let boards = columns(stats_width, rows[1]);
+        let stats = columns(stats_width, rows[3]);
+        for (player, board, stats) in [
+            (CurrentPlayer::First, boards[1], stats[1]),
+            (CurrentPlayer::Second, boards[3], stats[3]),
+        ] {
+            // the boards sit in the middle of the wider stats columns
+            let [_, board, _] = Layout::horizontal([
+                Constraint::Min(0),
+                Constraint::Length(width),
+                Constraint::Min(0),
+            ])
+            .areas(board);
+            let grid = record.board_at(player, record.shots.len());
+            BoardView::new(&grid, None, &record.player(player).name).render(f, board);
+            f.render_widget(self.stats_text(player), stats);
+        }
+
+        self.menu.render(f, rows[4]);
+    }
+}
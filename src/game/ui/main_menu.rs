@@ -0,0 +1,298 @@
+use std::path::PathBuf;
+
+use crossterm::event::{self, Event};
+use ratatui::DefaultTerminal;
+
+use crate::game::{
+    config::{ExtraTurn, MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    free_for_all::{MAX_PLAYERS, MIN_PLAYERS},
+    players::{Difficulty, Layout},
+    record,
+    ui::menu::{Menu, MenuEvent, TextInput},
+    weapon::Arsenal,
+};
+
+const MAX_NAME_LEN: usize = 20;
+const SERIES_LENGTHS: [usize; 4] = [1, 3, 5, 7];
+// charges of every special weapon the settings can hand out
+const MAX_CHARGES: usize = 3;
+pub const DEFAULT_PORT: u16 = 7878;
+
+// What the player picked in the main menu.
+#[derive(Clone)]
+pub enum Choice {
+    Continue,
+    VsComputer { name: String },
+    Hotseat { names: [String; 2] },
+    FreeForAll { name: String },
+    Host { name: String, addr: String },
+    Join { name: String, addr: String },
+    Replay(PathBuf),
+    Quit,
+}
+
+// Everything the settings screen changes, the command line fills in the starting values.
+pub struct Settings {
+    pub difficulty: Difficulty,
+    // None lets the difficulty pick the layout
+    pub layout: Option<Layout>,
+    pub width: usize,
+    pub height: usize,
+    // games against the computer or hotseat are played as a best of this many
+    pub best_of: usize,
+    pub salvo: bool,
+    pub no_touch: bool,
+    pub extra_turn: ExtraTurn,
+    // special attack charges every player starts with
+    pub weapons: Arsenal,
+    pub moving: bool,
+    pub repair: bool,
+    pub simultaneous: bool,
+    // everyone in a free-for-all, the player included
+    pub players: usize,
+}
+
+enum Entry {
+    Continue,
+    VsComputer,
+    Hotseat,
+    FreeForAll,
+    Host,
+    Join,
+    Replay,
+    Settings,
+    Quit,
+}
+impl Entry {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Continue => "Continue saved game",
+            Self::VsComputer => "Play against the computer",
+            Self::Hotseat => "Two players, one screen",
+            Self::FreeForAll => "Free-for-all against the computer",
+            Self::Host => "Host a network game",
+            Self::Join => "Join a network game",
+            Self::Replay => "Watch a replay",
+            Self::Settings => "Settings",
+            Self::Quit => "Quit",
+        }
+    }
+}
+
+// Shows the main menu until something to play or watch is picked.
+pub fn run(terminal: &mut DefaultTerminal, settings: &mut Settings, saved: bool) -> Choice {
+    let mut entries = vec![
+        Entry::VsComputer,
+        Entry::Hotseat,
+        Entry::FreeForAll,
+        Entry::Host,
+        Entry::Join,
+        Entry::Replay,
+        Entry::Settings,
+        Entry::Quit,
+    ];
+    if saved {
+        entries.insert(0, Entry::Continue);
+    }
+    let labels = entries.iter().map(|e| String::from(e.label())).collect();
+    let mut menu = Menu::new("Battleships", labels);
+    loop {
+        let entry = match pick(terminal, &mut menu) {
+            MenuEvent::Select(i) => &entries[i],
+            MenuEvent::Back => return Choice::Quit,
+            MenuEvent::Left(_) | MenuEvent::Right(_) => continue,
+        };
+        let choice = match entry {
+            Entry::Continue => Some(Choice::Continue),
+            Entry::VsComputer => {
+                ask(terminal, "Your name", "Player 1").map(|name| Choice::VsComputer { name })
+            }
+            Entry::Hotseat => ask(terminal, "Player 1's name", "Player 1").and_then(|first| {
+                ask(terminal, "Player 2's name", "Player 2").map(|second| Choice::Hotseat {
+                    names: [first, second],
+                })
+            }),
+            Entry::FreeForAll => {
+                ask(terminal, "Your name", "Player 1").map(|name| Choice::FreeForAll { name })
+            }
+            Entry::Host => ask(terminal, "Your name", "Player 1").and_then(|name| {
+                ask(
+                    terminal,
+                    "Address to listen on",
+                    &format!("0.0.0.0:{DEFAULT_PORT}"),
+                )
+                .map(|addr| Choice::Host { name, addr })
+            }),
+            Entry::Join => ask(terminal, "Your name", "Player 2").and_then(|name| {
+                ask(
+                    terminal,
+                    "Address of the host",
+                    &format!("127.0.0.1:{DEFAULT_PORT}"),
+                )
+                .map(|addr| Choice::Join { name, addr })
+            }),
+            Entry::Replay => pick_record(terminal).map(Choice::Replay),
+            Entry::Settings => {
+                edit_settings(terminal, settings);
+                None
+            }
+            Entry::Quit => Some(Choice::Quit),
+        };
+        if let Some(choice) = choice {
+            return choice;
+        }
+    }
+}
+
+fn pick(terminal: &mut DefaultTerminal, menu: &mut Menu) -> MenuEvent {
+    loop {
+        let _ = terminal.draw(|f| menu.render(f, f.area()));
+        let Ok(Event::Key(key)) = event::read() else {
+            continue;
+        };
+        if let Some(event) = menu.handle_key(key) {
+            return event;
+        }
+    }
+}
+
+fn ask(terminal: &mut DefaultTerminal, prompt: &str, default: &str) -> Option<String> {
+    TextInput::new(prompt, default, MAX_NAME_LEN.max(default.len())).run(terminal)
+}
+
+fn pick_record(terminal: &mut DefaultTerminal) -> Option<PathBuf> {
+    let mut records: Vec<PathBuf> = std::fs::read_dir(record::default_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    // the names start with when the game was played, newest first
+    records.sort();
+    records.reverse();
+    let mut labels: Vec<String> = records
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    labels.push(String::from("Back"));
+    let mut menu = Menu::new("Recorded games", labels);
+    match pick(terminal, &mut menu) {
+        MenuEvent::Select(i) => records.get(i).cloned(),
+        _ => None,
+    }
+}
+
+fn edit_settings(terminal: &mut DefaultTerminal, settings: &mut Settings) {
+    let labels = |settings: &Settings| {
+        vec![
+            format!("Difficulty: < {} >", settings.difficulty),
+            format!(
+                "Computer fleet layout: < {} >",
+                settings
+                    .layout
+                    .map_or(String::from("by difficulty"), |l| l.to_string())
+            ),
+            format!("Board width: < {} >", settings.width),
+            format!("Board height: < {} >", settings.height),
+            format!("Series: < best of {} >", settings.best_of),
+            format!("Salvo: < {} >", on_off(settings.salvo)),
+            format!("Ships may touch: < {} >", on_off(!settings.no_touch)),
+            format!("Extra shot on: < {} >", settings.extra_turn),
+            format!("Special weapons: < {} >", charges(&settings.weapons)),
+            format!("Moving ships: < {} >", on_off(settings.moving)),
+            format!("Repairs: < {} >", on_off(settings.repair)),
+            format!("Simultaneous turns: < {} >", on_off(settings.simultaneous)),
+            format!("Free-for-all players: < {} >", settings.players),
+            String::from("Back"),
+        ]
+    };
+    let mut menu = Menu::new("Settings", labels(settings));
+    loop {
+        let (item, step) = match pick(terminal, &mut menu) {
+            MenuEvent::Back | MenuEvent::Select(13) => return,
+            MenuEvent::Left(i) => (i, -1),
+            MenuEvent::Right(i) | MenuEvent::Select(i) => (i, 1),
+        };
+        match item {
+            0 => settings.difficulty = cycle(&Difficulty::ALL, settings.difficulty, step),
+            1 => {
+                // None sits before the first layout
+                let options: Vec<Option<Layout>> =
+                    [None].into_iter().chain(Layout::ALL.map(Some)).collect();
+                settings.layout = cycle(&options, settings.layout, step);
+            }
+            2 => settings.width = resize(settings.width, step),
+            3 => settings.height = resize(settings.height, step),
+            4 => settings.best_of = cycle(&SERIES_LENGTHS, settings.best_of, step),
+            5 => settings.salvo = !settings.salvo,
+            6 => settings.no_touch = !settings.no_touch,
+            7 => settings.extra_turn = cycle(&ExtraTurn::ALL, settings.extra_turn, step),
+            8 => {
+                // every weapon gets the same number of charges from here
+                let most = settings.weapons.radar.max(settings.weapons.torpedo);
+                let most = most.max(settings.weapons.airstrike).min(MAX_CHARGES);
+                let counts: Vec<usize> = (0..=MAX_CHARGES).collect();
+                settings.weapons = Arsenal::each(cycle(&counts, most, step));
+            }
+            9 => settings.moving = !settings.moving,
+            10 => settings.repair = !settings.repair,
+            11 => settings.simultaneous = !settings.simultaneous,
+            12 => {
+                let counts: Vec<usize> = (MIN_PLAYERS..=MAX_PLAYERS).collect();
+                settings.players = cycle(&counts, settings.players, step);
+            }
+            _ => {}
+        }
+        menu.set_items(labels(settings));
+    }
+}
+
+fn charges(arsenal: &Arsenal) -> String {
+    if arsenal.is_empty() {
+        return String::from("off");
+    }
+    format!(
+        "radar {}, torpedo {}, airstrike {}",
+        arsenal.radar, arsenal.torpedo, arsenal.airstrike
+    )
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let i = options.iter().position(|o| *o == current).unwrap_or(0);
+    options[(i as isize + step).rem_euclid(options.len() as isize) as usize]
+}
+
+fn resize(size: usize, step: isize) -> usize {
+    size.saturating_add_signed(step)
+        .clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        config::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+        players::Difficulty,
+        ui::main_menu::{cycle, resize},
+    };
+
+    #[test]
+    fn settings_wrap_and_clamp() {
+        assert_eq!(
+            cycle(&Difficulty::ALL, Difficulty::Easy, -1),
+            Difficulty::Brutal
+        );
+        assert_eq!(
+            cycle(&Difficulty::ALL, Difficulty::Brutal, 1),
+            Difficulty::Easy
+        );
+        assert_eq!(resize(MIN_BOARD_SIZE, -1), MIN_BOARD_SIZE);
+        assert_eq!(resize(MAX_BOARD_SIZE, 1), MAX_BOARD_SIZE);
+        assert_eq!(resize(10, 1), 11);
+    }
+}
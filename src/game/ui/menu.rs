@@ -0,0 +1,189 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style},
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Paragraph},
+};
+
+pub enum MenuEvent {
+    Select(usize),
+    // left and right change the value of the highlighted item, for settings
+    Left(usize),
+    Right(usize),
+    Back,
+}
+
+// A bordered list of entries, moved through with the arrow keys.
+pub struct Menu {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+}
+impl Menu {
+    pub fn new(title: &str, items: Vec<String>) -> Self {
+        Self {
+            title: String::from(title),
+            items,
+            selected: 0,
+        }
+    }
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.selected = self.selected.min(items.len().saturating_sub(1));
+        self.items = items;
+    }
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<MenuEvent> {
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(self.items.len() - 1),
+            KeyCode::Enter => return Some(MenuEvent::Select(self.selected)),
+            KeyCode::Left => return Some(MenuEvent::Left(self.selected)),
+            KeyCode::Right => return Some(MenuEvent::Right(self.selected)),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(MenuEvent::Back),
+            _ => {}
+        }
+        None
+    }
+    pub fn render(&self, f: &mut Frame, rect: Rect) {
+        let lines: Vec<Line> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                if i == self.selected {
+                    Line::from(format!("> {item} <")).style(Style::new().fg(Color::Yellow))
+                } else {
+                    Line::from(item.as_str())
+                }
+            })
+            .collect();
+        let width = self
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain([self.title.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 6;
+        let area = centered(rect, width, lines.len() as u16 + 2);
+        let block = Block::bordered()
+            .title(Line::from(self.title.as_str()).centered())
+            .border_set(border::THICK);
+        f.render_widget(
+            Paragraph::new(Text::from(lines).centered()).block(block),
+            area,
+        );
+    }
+}
+
+pub enum InputEvent {
+    Submit(String),
+    Cancel,
+}
+
+// A single line of text, used for names and addresses.
+pub struct TextInput {
+    prompt: String,
+    value: String,
+    max_len: usize,
+}
+impl TextInput {
+    pub fn new(prompt: &str, value: &str, max_len: usize) -> Self {
+        Self {
+            prompt: String::from(prompt),
+            value: String::from(value),
+            max_len,
+        }
+    }
+    // None when the player backed out
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> Option<String> {
+        loop {
+            let _ = terminal.draw(|f| self.render(f, f.area()));
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            match self.handle_key(key) {
+                Some(InputEvent::Submit(text)) => return Some(text),
+                Some(InputEvent::Cancel) => return None,
+                None => {}
+            }
+        }
+    }
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<InputEvent> {
+        match key.code {
+            KeyCode::Char(ch) if self.value.chars().count() < self.max_len => self.value.push(ch),
+            KeyCode::Backspace => {
+                self.value.pop();
+            }
+            KeyCode::Enter if !self.value.trim().is_empty() => {
+                return Some(InputEvent::Submit(self.value.trim().to_string()));
+            }
+            KeyCode::Esc => return Some(InputEvent::Cancel),
+            _ => {}
+        }
+        None
+    }
+    pub fn render(&self, f: &mut Frame, rect: Rect) {
+        let width = self.prompt.chars().count().max(self.max_len) as u16 + 4;
+        let area = centered(rect, width, 3);
+        let block = Block::bordered()
+            .title(Line::from(self.prompt.as_str()).centered())
+            .border_set(border::THICK);
+        let text = Line::from(format!("{}_", self.value));
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+}
+
+fn centered(rect: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(rect);
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    use crate::game::ui::menu::{InputEvent, Menu, MenuEvent, TextInput};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn menu_selection_stays_in_range() {
+        let mut menu = Menu::new("menu", vec![String::from("a"), String::from("b")]);
+        menu.handle_key(key(KeyCode::Up));
+        menu.handle_key(key(KeyCode::Down));
+        menu.handle_key(key(KeyCode::Down));
+        assert!(matches!(
+            menu.handle_key(key(KeyCode::Enter)),
+            Some(MenuEvent::Select(1))
+        ));
+        menu.set_items(vec![String::from("only")]);
+        assert!(matches!(
+            menu.handle_key(key(KeyCode::Enter)),
+            Some(MenuEvent::Select(0))
+        ));
+    }
+    #[test]
+    fn input_needs_some_text() {
+        let mut input = TextInput::new("name", "", 3);
+        assert!(input.handle_key(key(KeyCode::Enter)).is_none());
+        for ch in "page".chars() {
+            input.handle_key(key(KeyCode::Char(ch)));
+        }
+        input.handle_key(key(KeyCode::Backspace));
+        input.handle_key(key(KeyCode::Char('e')));
+        assert!(matches!(
+            input.handle_key(key(KeyCode::Enter)),
+            Some(InputEvent::Submit(name)) if name == "pae"
+        ));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod game_over;
+pub mod main_menu;
+pub mod menu;
+pub mod replay;
 pub mod wait_for_key_press;
+pub use replay::ReplayViewer;
 pub use wait_for_key_press::WaitForKey;
@@ -0,0 +1,161 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Text},
+};
+
+use crate::game::{
+    CurrentPlayer,
+    cursor::Cursor,
+    player_board::{ShotOutcome, board_view::BoardView},
+    record::GameRecord,
+};
+
+// Steps through a recorded game, showing both boards as they were after every shot.
+pub struct ReplayViewer {
+    record: GameRecord,
+    // how many shots have been played
+    step: usize,
+}
+
+impl ReplayViewer {
+    pub fn new(record: GameRecord) -> Self {
+        Self { record, step: 0 }
+    }
+    pub fn run(mut self, terminal: &mut DefaultTerminal) {
+        loop {
+            let _ = terminal.draw(|f| self.render(f, f.area()));
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if !self.handle_key(key) {
+                break;
+            }
+        }
+    }
+    // returns false once the viewer should close
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let last = self.record.shots.len();
+        match key.code {
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                self.step = (self.step + 1).min(last)
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => {
+                self.step = self.step.saturating_sub(1)
+            }
+            KeyCode::Home => self.step = 0,
+            KeyCode::End => self.step = last,
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        true
+    }
+    fn status(&self) -> String {
+        let total = self.record.shots.len();
+        let Some(shot) = self.step.checked_sub(1).map(|i| &self.record.shots[i]) else {
+            return format!("Start of the game, {total} shots were fired");
+        };
+        let result = match shot.result {
+            ShotOutcome::Hit => "hit",
+            ShotOutcome::Damaged => "damaged",
+            ShotOutcome::Miss => "miss",
+            ShotOutcome::Sunk { .. } => "sunk",
+            ShotOutcome::Decoy { .. } => "destroyed a decoy",
+            ShotOutcome::Mine => "set off a mine",
+        };
+        let mut status = format!(
+            "Shot {}/{total} at {:.1}s: {} fired at ({}, {}), {result}",
+            self.step,
+            shot.time as f64 / 1000.0,
+            self.record.player(shot.player).name,
+            shot.at.x,
+            shot.at.y,
+        );
+        // recorded games are finished, one without a winner was a draw
+        if self.step == total {
+            match self.record.winner {
+                Some(winner) => {
+                    status.push_str(&format!(", {} won", self.record.player(winner).name))
+                }
+                None => status.push_str(", it's a draw"),
+            }
+        }
+        status
+    }
+    pub fn render(&self, f: &mut Frame, rect: Rect) {
+        let (width, height) = (self.record.width as u16 + 2, self.record.height as u16 + 2);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(height),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(width),
+                Constraint::Length(2),
+                Constraint::Length(width),
+                Constraint::Min(0),
+            ])
+            .split(rows[1]);
+
+        let last_shot = self.step.checked_sub(1).map(|i| &self.record.shots[i]);
+        for (player, area) in [
+            (CurrentPlayer::First, columns[1]),
+            (CurrentPlayer::Second, columns[3]),
+        ] {
+            let grid = self.record.board_at(player, self.step);
+            // the cell that was just shot at is highlighted
+            let cursor = last_shot
+                .filter(|shot| shot.player != player)
+                .map(|shot| Cursor::new(shot.at.x, shot.at.y, grid[0].len(), grid.len()));
+            let view = BoardView::new(&grid, cursor, &self.record.player(player).name);
+            view.render(f, area);
+        }
+
+        let text = Text::from(vec![
+            Line::from(self.status()),
+            Line::from(""),
+            Line::from("left/right: step, home/end: jump, q: quit"),
+        ])
+        .centered();
+        f.render_widget(text, rows[2]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    use crate::game::{
+        CurrentPlayer, config::GameConfig, player_board::ShotOutcome, point::Point,
+        record::GameRecord, ui::ReplayViewer,
+    };
+
+    #[test]
+    fn steps_stay_within_the_record() {
+        let mut record = GameRecord::new(&GameConfig::default());
+        record.shot(CurrentPlayer::First, Point::new(1, 1), ShotOutcome::Miss);
+        record.shot(CurrentPlayer::Second, Point::new(2, 2), ShotOutcome::Hit);
+        let mut viewer = ReplayViewer::new(record);
+        let press = |viewer: &mut ReplayViewer, code| viewer.handle_key(KeyEvent::from(code));
+
+        assert!(press(&mut viewer, KeyCode::Left));
+        assert_eq!(viewer.step, 0);
+        press(&mut viewer, KeyCode::Right);
+        press(&mut viewer, KeyCode::Right);
+        press(&mut viewer, KeyCode::Right);
+        assert_eq!(viewer.step, 2);
+        press(&mut viewer, KeyCode::Home);
+        assert_eq!(viewer.step, 0);
+        press(&mut viewer, KeyCode::End);
+        assert!(viewer.status().starts_with("Shot 2/2"));
+        assert!(!press(&mut viewer, KeyCode::Char('q')));
+    }
+}
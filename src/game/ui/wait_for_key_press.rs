@@ -1,5 +1,5 @@
 use crossterm::event::{self, Event};
-use ratatui::{Frame, layout::Rect, text::Text, widgets::Widget};
+use ratatui::{Frame, layout::Rect, text::Text};
 
 pub struct WaitForKey<'a> {
     text: Text<'a>,
@@ -20,16 +20,15 @@ impl<'a> WaitForKey<'a> {
             let Ok(Event::Key(key)) = event::read() else {
                 continue;
             };
-            // let Some(ch) = key.code.as_char() else {
-            //     continue;
-            // };
-            // if let Some(ref allowed) = allowed {
-            //     if !allowed.contains(&ch) {
-            //         continue;
-            //     }
-            // }
-            break 't';
-            // break ch;
+            let Some(ref allowed) = allowed else {
+                break key.code.as_char().unwrap_or(' ');
+            };
+            let Some(ch) = key.code.as_char() else {
+                continue;
+            };
+            if allowed.contains(&ch) {
+                break ch;
+            }
         }
     }
 }
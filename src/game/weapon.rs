@@ -0,0 +1,170 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{player_board::ShotOutcome, point::Point};
+
+// A special attack, fired instead of the normal shot of a turn while there are charges left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Weapon {
+    // counts the ship cells in the 3x3 square around the target, nothing is shot at
+    Radar,
+    // runs from the target towards the right edge and stops at the first ship it meets
+    Torpedo,
+    // shoots the target and the four cells next to it
+    Airstrike,
+}
+impl Weapon {
+    pub const ALL: [Self; 3] = [Self::Radar, Self::Torpedo, Self::Airstrike];
+    // the cells the weapon reaches when aimed at p, the torpedo's in the order it passes them
+    pub fn area(self, p: Point, width: usize, height: usize) -> Vec<Point> {
+        let offsets: &[(isize, isize)] = match self {
+            Self::Radar => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (0, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Self::Airstrike => &[(0, 0), (0, -1), (-1, 0), (1, 0), (0, 1)],
+            Self::Torpedo => return (p.x..width).map(|x| Point::new(x, p.y)).collect(),
+        };
+        offsets
+            .iter()
+            .filter_map(|&(dx, dy)| p.offset(dx, dy))
+            .filter(|q| q.x < width && q.y < height)
+            .collect()
+    }
+    // whether the shots reported for this weapon are the ones it fires, given the cells of its
+    // area that weren't shot at before, in order
+    pub fn fired_at(self, fresh: &[Point], results: &[(Point, ShotOutcome)]) -> bool {
+        let cells: Vec<Point> = results.iter().map(|(p, _)| *p).collect();
+        match self {
+            Self::Radar => false,
+            Self::Airstrike => cells == fresh,
+            // every cell up to the first ship is water, and it only stops early at a ship
+            Self::Torpedo => {
+                let Some(((_, last), crossed)) = results.split_last() else {
+                    return false;
+                };
+                fresh.starts_with(&cells)
+                    && crossed
+                        .iter()
+                        .all(|(_, result)| *result == ShotOutcome::Miss)
+                    && (*last != ShotOutcome::Miss || cells.len() == fresh.len())
+            }
+        }
+    }
+}
+impl Display for Weapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Radar => "radar",
+            Self::Torpedo => "torpedo",
+            Self::Airstrike => "airstrike",
+        })
+    }
+}
+
+// How many charges of every weapon a player has, the rules say what each game starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Arsenal {
+    pub radar: usize,
+    pub torpedo: usize,
+    pub airstrike: usize,
+}
+impl Arsenal {
+    // the same number of charges of every weapon
+    pub fn each(charges: usize) -> Self {
+        Self {
+            radar: charges,
+            torpedo: charges,
+            airstrike: charges,
+        }
+    }
+    pub fn charges(&self, weapon: Weapon) -> usize {
+        match weapon {
+            Weapon::Radar => self.radar,
+            Weapon::Torpedo => self.torpedo,
+            Weapon::Airstrike => self.airstrike,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        Weapon::ALL.iter().all(|&weapon| self.charges(weapon) == 0)
+    }
+    // uses up one charge, false when there was none left
+    pub fn take(&mut self, weapon: Weapon) -> bool {
+        let charges = match weapon {
+            Weapon::Radar => &mut self.radar,
+            Weapon::Torpedo => &mut self.torpedo,
+            Weapon::Airstrike => &mut self.airstrike,
+        };
+        let Some(left) = charges.checked_sub(1) else {
+            return false;
+        };
+        *charges = left;
+        true
+    }
+}
+
+// What a special attack did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Strike {
+    // how many ship cells, hit or not, the radar found
+    Ping { count: usize },
+    // every cell that was shot at, in the order they were resolved
+    Shots { results: Vec<(Point, ShotOutcome)> },
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{
+        player_board::ShotOutcome,
+        point::Point,
+        weapon::{Arsenal, Weapon},
+    };
+
+    #[test]
+    fn areas_are_cut_off_at_the_edges() {
+        assert_eq!(Weapon::Radar.area(Point::new(0, 0), 10, 10).len(), 4);
+        assert_eq!(Weapon::Radar.area(Point::new(5, 5), 10, 10).len(), 9);
+        assert_eq!(Weapon::Airstrike.area(Point::new(9, 0), 10, 10).len(), 3);
+        assert_eq!(
+            Weapon::Torpedo.area(Point::new(7, 2), 10, 10),
+            vec![Point::new(7, 2), Point::new(8, 2), Point::new(9, 2)]
+        );
+    }
+    #[test]
+    fn torpedo_only_stops_at_a_ship() {
+        let fresh = [Point::new(7, 2), Point::new(8, 2), Point::new(9, 2)];
+        let stopped = [
+            (Point::new(7, 2), ShotOutcome::Miss),
+            (Point::new(8, 2), ShotOutcome::Hit),
+        ];
+        assert!(Weapon::Torpedo.fired_at(&fresh, &stopped));
+        assert!(!Weapon::Torpedo.fired_at(&fresh, &stopped[..1]));
+        let through = fresh.map(|p| (p, ShotOutcome::Miss));
+        assert!(Weapon::Torpedo.fired_at(&fresh, &through));
+        assert!(!Weapon::Torpedo.fired_at(&fresh, &[]));
+        assert!(!Weapon::Airstrike.fired_at(&fresh, &stopped));
+    }
+    #[test]
+    fn charges_run_out() {
+        let mut arsenal = Arsenal {
+            torpedo: 1,
+            ..Arsenal::default()
+        };
+        assert!(!arsenal.take(Weapon::Radar));
+        assert!(arsenal.take(Weapon::Torpedo));
+        assert!(!arsenal.take(Weapon::Torpedo));
+        assert!(arsenal.is_empty());
+        assert_eq!(Arsenal::each(2).charges(Weapon::Airstrike), 2);
+    }
+}
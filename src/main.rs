@@ -1,53 +1,441 @@
 mod game;
 use std::cell::RefCell;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 use std::rc::Rc;
 
-use crate::game::PlayerVsPlayerMode;
-use game::players::{Computer, Player};
-use game::point::Point;
-use game::ship::ShipBlueprint;
-use game::{GameMode, Setup, SinglePlayer};
+use game::config::{ExtraTurn, GameConfig};
+use game::free_for_all::{self, Contender, FreeForAll, FreeForAllResult};
+use game::net;
+use game::players::{Computer, Difficulty, Layout, Player};
+use game::record::{self, GameRecord};
+use game::rng;
+use game::save::{self, SavedGame};
+use game::series::Series;
+use game::ui::game_over::{AfterGame, GameOverScreen};
+use game::ui::main_menu::{self, Choice, Settings};
+use game::ui::{ReplayViewer, WaitForKey};
+use game::weapon::Arsenal;
+use game::{GameEnd, GameMode, PlayerVsPlayerMode, Setup, SinglePlayer};
+use ratatui::DefaultTerminal;
+use ratatui::text::Text;
+
+const USAGE: &str = "usage: battleships [--hotseat | --free-for-all PLAYERS | --host ADDR | --join ADDR | --replay RECORD] [--difficulty easy|normal|hard|brutal] [--layout random|spread-out|edge-hugging|anti-heatmap] [--size WIDTHxHEIGHT] [--best-of N] [--salvo] [--no-touch] [--extra-turn never|hit|sink] [--weapons N] [--moving] [--repair] [--simultaneous] [--seed SEED] [FLEET_FILE]";
+
+enum Network {
+    Host(String),
+    Join(String),
+}
+
 fn main() {
-    let terminal = ratatui::init();
-
-    let term = Rc::new(RefCell::new(terminal));
-
-    let p1: Player = Player::new(Rc::clone(&term), String::from("mistr"));
-
-    // let p2: Player = Player::new(Rc::clone(&term), String::from("page"));
-    // let mut game = PlayerVsPlayerMode::new(p1, p2, term);
-
-    let p2: Computer = Computer::new();
-    let mut game = SinglePlayer::new(p1, p2);
-
-    // TODO: Hardcode at first then add loading from config?
-    let ships: Vec<ShipBlueprint> = vec![
-        // ShipBlueprint::new(vec![Point::new(0, 0)], String::from("dot")),
-        ShipBlueprint::new(
-            vec![Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)],
-            String::from("new"),
-        ),
-        ShipBlueprint::new(
-            vec![
-                Point::new(0, 0),
-                Point::new(1, 0),
-                Point::new(2, 0),
-                Point::new(3, 0),
-            ],
-            String::from("long boy"),
-        ),
-        ShipBlueprint::new(
-            vec![Point::new(0, 0), Point::new(0, 1), Point::new(1, 0)],
-            String::from("skibidi"),
-        ),
-    ];
-    game.setup(ships);
-    let player = game.run();
+    let mut hotseat = false;
+    let mut free_for_all: Option<usize> = None;
+    let mut network: Option<Network> = None;
+    let mut replay: Option<PathBuf> = None;
+    let mut fleet_path: Option<PathBuf> = None;
+    let mut board_size: Option<(usize, usize)> = None;
+    let mut difficulty = Difficulty::default();
+    let mut layout: Option<Layout> = None;
+    let mut seed: Option<u64> = None;
+    let mut best_of = 1;
+    let mut salvo = false;
+    let mut no_touch = false;
+    let mut moving = false;
+    let mut repair = false;
+    let mut simultaneous = false;
+    let mut extra_turn: Option<ExtraTurn> = None;
+    let mut weapons: Option<usize> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hotseat" => hotseat = true,
+            "--salvo" => salvo = true,
+            "--no-touch" => no_touch = true,
+            "--moving" => moving = true,
+            "--repair" => repair = true,
+            "--simultaneous" => simultaneous = true,
+            "--host" | "--join" => {
+                let Some(addr) = args.next() else {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                };
+                network = Some(if arg == "--host" {
+                    Network::Host(addr)
+                } else {
+                    Network::Join(addr)
+                });
+            }
+            "--free-for-all" => match args.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(n) if (free_for_all::MIN_PLAYERS..=free_for_all::MAX_PLAYERS).contains(&n) => {
+                    free_for_all = Some(n)
+                }
+                _ => {
+                    eprintln!(
+                        "--free-for-all needs {} to {} players",
+                        free_for_all::MIN_PLAYERS,
+                        free_for_all::MAX_PLAYERS
+                    );
+                    exit(2);
+                }
+            },
+            "--replay" => {
+                replay = args.next().map(PathBuf::from);
+                if replay.is_none() {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            }
+            "--difficulty" => match args.next().map(|d| d.parse::<Difficulty>()) {
+                Some(Ok(d)) => difficulty = d,
+                Some(Err(err)) => {
+                    eprintln!("{err}");
+                    exit(2);
+                }
+                None => {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            },
+            "--layout" => match args.next().map(|l| l.parse::<Layout>()) {
+                Some(Ok(l)) => layout = Some(l),
+                Some(Err(err)) => {
+                    eprintln!("{err}");
+                    exit(2);
+                }
+                None => {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            },
+            "--extra-turn" => match args.next().map(|e| e.parse::<ExtraTurn>()) {
+                Some(Ok(e)) => extra_turn = Some(e),
+                Some(Err(err)) => {
+                    eprintln!("{err}");
+                    exit(2);
+                }
+                None => {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            },
+            "--weapons" => {
+                weapons = args.next().and_then(|n| n.parse().ok());
+                if weapons.is_none() {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            }
+            "--seed" => {
+                seed = args.next().and_then(|s| s.parse().ok());
+                if seed.is_none() {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            }
+            "--best-of" => match args.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(n) if !n.is_multiple_of(2) => best_of = n,
+                _ => {
+                    eprintln!("--best-of needs an odd number of games");
+                    exit(2);
+                }
+            },
+            "--size" => {
+                board_size = args.next().as_deref().and_then(parse_size);
+                if board_size.is_none() {
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return;
+            }
+            _ if fleet_path.is_none() && !arg.starts_with('-') => {
+                fleet_path = Some(PathBuf::from(arg))
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                exit(2);
+            }
+        }
+    }
+    let config = match fleet_path {
+        Some(path) => GameConfig::load(&path),
+        None => Ok(GameConfig::default()),
+    };
+    let config = match board_size {
+        Some((width, height)) => config.and_then(|c| c.with_board_size(width, height)),
+        None => config,
+    };
+    let config = config.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        exit(1);
+    });
+    let seed = seed.unwrap_or_else(rng::random_seed);
+    let config = config.with_seed(seed);
+    let mut settings = Settings {
+        difficulty,
+        layout,
+        width: config.rules.width,
+        height: config.rules.height,
+        best_of,
+        salvo: salvo || config.rules.salvo,
+        no_touch: no_touch || config.rules.no_touch,
+        extra_turn: extra_turn.unwrap_or(config.rules.extra_turn),
+        weapons: weapons.map_or(config.rules.weapons, Arsenal::each),
+        moving: moving || config.rules.moving,
+        repair: repair || config.rules.repair,
+        simultaneous: simultaneous || config.rules.simultaneous,
+        players: free_for_all.unwrap_or(4),
+    };
+    let save_path = save::default_path();
+
+    // a mode on the command line skips the menu
+    let picked = if let Some(path) = replay {
+        Some(Choice::Replay(path))
+    } else if let Some(network) = network {
+        Some(match network {
+            Network::Host(addr) => Choice::Host {
+                name: String::from("Player 1"),
+                addr,
+            },
+            Network::Join(addr) => Choice::Join {
+                name: String::from("Player 2"),
+                addr,
+            },
+        })
+    } else if free_for_all.is_some() {
+        Some(Choice::FreeForAll {
+            name: String::from("Player 1"),
+        })
+    } else if hotseat {
+        Some(Choice::Hotseat {
+            names: [String::from("Player 1"), String::from("Player 2")],
+        })
+    } else {
+        None
+    };
+    // errors are shown in the menu once it has been opened, before that they end the program
+    let mut from_menu = false;
+    let mut picked = picked;
 
-    // TODO: Game Over screen
+    let term = Rc::new(RefCell::new(ratatui::init()));
+
+    let mut config = config;
+    // printed once the terminal is given back, errors go to stderr
+    let message: Option<Result<String, String>> = loop {
+        let choice = match picked.take() {
+            Some(choice) => choice,
+            None => {
+                from_menu = true;
+                main_menu::run(&mut term.borrow_mut(), &mut settings, save_path.exists())
+            }
+        };
+        if let Choice::Quit = choice {
+            break None;
+        }
+        let resumed = matches!(choice, Choice::Continue);
+        // network games end with their connection and continued games with their save
+        let rematch = matches!(choice, Choice::VsComputer { .. } | Choice::Hotseat { .. })
+            .then(|| choice.clone());
+        let end = match start(choice, &term, &config, &settings, &save_path) {
+            Ok(Some(end)) => end,
+            Ok(None) if from_menu => continue,
+            Ok(None) => break None,
+            Err(err) if from_menu => {
+                let wait = WaitForKey::new(Text::from(format!("{err}\n\npress any key")));
+                let _ = term.borrow_mut().draw(|f| wait.render(f, f.area()));
+                wait.wait(None);
+                continue;
+            }
+            Err(err) => {
+                ratatui::restore();
+                eprintln!("{err}");
+                exit(1);
+            }
+        };
+        let result = match end {
+            GameEnd::Over(result) => *result,
+            GameEnd::Quit(Some(saved)) => {
+                break Some(match saved.write(&save_path) {
+                    Ok(()) => Ok(format!("Game saved to {}", save_path.display())),
+                    Err(err) => Err(format!("{}: {err}", save_path.display())),
+                });
+            }
+            GameEnd::Quit(None) => {
+                break Some(Ok(String::from(
+                    "Network games can't be saved, the game was abandoned",
+                )));
+            }
+        };
+        if resumed {
+            // the saved game is finished now
+            let _ = std::fs::remove_file(&save_path);
+        }
+        // the earlier games of a series are recorded too, the status points at the last one
+        let records = result.series.iter().flat_map(|score| &score.earlier);
+        let written: Result<Vec<PathBuf>, _> = records
+            .chain([&result.record])
+            .map(|record| record.write_to(&record::default_dir()))
+            .collect();
+        let status = match written.map(|paths| paths.last().cloned()) {
+            Ok(Some(path)) => format!(
+                "Watch it again with --replay {}, play it again with --seed {}",
+                path.display(),
+                result.record.seed
+            ),
+            Ok(None) => String::new(),
+            Err(err) => format!("could not record the game: {err}"),
+        };
+        let screen = GameOverScreen::new(result, status, rematch.is_some());
+        match screen.run(&mut term.borrow_mut()) {
+            AfterGame::Rematch => {
+                // a new seed, or the rematch would be the same game again
+                config = config.with_seed(rng::random_seed());
+                picked = rematch;
+            }
+            AfterGame::Menu => {}
+            AfterGame::Quit => break None,
+        }
+    };
     ratatui::restore();
-    println!(
-        "Player {}: {} has won the game!",
-        player.winner as i32, player.winner_name
-    );
+    match message {
+        Some(Ok(message)) => println!("{message}"),
+        Some(Err(err)) => eprintln!("{err}"),
+        None => {}
+    }
+}
+
+// Plays or shows what was picked, None when there is no game to report on.
+fn start(
+    choice: Choice,
+    term: &Rc<RefCell<DefaultTerminal>>,
+    config: &GameConfig,
+    settings: &Settings,
+    save_path: &Path,
+) -> Result<Option<GameEnd>, String> {
+    let mut config = config.clone();
+    config.rules.salvo = settings.salvo;
+    config.rules.no_touch = settings.no_touch;
+    config.rules.extra_turn = settings.extra_turn;
+    config.rules.weapons = settings.weapons;
+    config.rules.moving = settings.moving;
+    config.rules.repair = settings.repair;
+    config.rules.simultaneous = settings.simultaneous;
+    let config = config
+        .with_board_size(settings.width, settings.height)
+        .map_err(|err| err.to_string())?;
+    let end = match choice {
+        Choice::Quit => return Ok(None),
+        Choice::Replay(path) => {
+            let record =
+                GameRecord::read(&path).map_err(|err| format!("{}: {err}", path.display()))?;
+            ReplayViewer::new(record).run(&mut term.borrow_mut());
+            return Ok(None);
+        }
+        Choice::Continue => SavedGame::read(save_path)
+            .and_then(|saved| saved.resume(Rc::clone(term)))
+            .map_err(|err| format!("{}: {err}", save_path.display()))?,
+        Choice::VsComputer { name } => {
+            let p1 = Player::new(Rc::clone(term), name);
+            let mut p2 = Computer::new(settings.difficulty);
+            if let Some(layout) = settings.layout {
+                p2 = p2.with_layout(layout);
+            }
+            let mut game = Series::new(SinglePlayer::new(p1, p2), settings.best_of)
+                .with_terminal(Rc::clone(term));
+            game.setup(config);
+            game.run()
+        }
+        Choice::Hotseat {
+            names: [first, second],
+        } => {
+            let p1 = Player::new(Rc::clone(term), first);
+            let p2 = Player::new(Rc::clone(term), second);
+            let game = PlayerVsPlayerMode::new(p1, p2, Rc::clone(term));
+            let mut game = Series::new(game, settings.best_of).with_terminal(Rc::clone(term));
+            game.setup(config);
+            game.run()
+        }
+        Choice::FreeForAll { name } => {
+            if let Some(rule) = free_for_all::unsupported(&config.rules) {
+                return Err(format!("{rule} can't be played in a free-for-all"));
+            }
+            let computers = (1..settings.players).map(|i| {
+                let computer = Contender::computer(settings.difficulty, format!("Clanker {i}"));
+                match settings.layout {
+                    Some(layout) => computer.with_layout(layout),
+                    None => computer,
+                }
+            });
+            let contenders = [Contender::human(Rc::clone(term), name)]
+                .into_iter()
+                .chain(computers)
+                .collect();
+            let mut game = FreeForAll::new(contenders);
+            game.setup(config);
+            if let Some(result) = game.play() {
+                let wait = WaitForKey::new(Text::from(standings(&result)));
+                let _ = term.borrow_mut().draw(|f| wait.render(f, f.area()));
+                wait.wait(None);
+            }
+            return Ok(None);
+        }
+        Choice::Host { name, addr } => {
+            let listener = TcpListener::bind(&addr).map_err(|err| format!("{addr}: {err}"))?;
+            let shown = listener.local_addr().map_or(addr, |addr| addr.to_string());
+            waiting(term, &format!("Waiting for an opponent on {shown}"));
+            let (p2, config) =
+                net::host(&listener, &name, config).map_err(|err| err.to_string())?;
+            let mut game = SinglePlayer::new(Player::new(Rc::clone(term), name), p2);
+            game.setup(config);
+            game.run()
+        }
+        Choice::Join { name, addr } => {
+            waiting(term, &format!("Connecting to {addr}"));
+            let (p2, config) =
+                net::join(addr, &name, config.seed).map_err(|err| err.to_string())?;
+            let mut game = SinglePlayer::new(Player::new(Rc::clone(term), name), p2);
+            game.setup(config);
+            game.run()
+        }
+    };
+    Ok(Some(end))
+}
+
+// shown while a network call blocks, there is no key to wait for
+fn waiting(term: &Rc<RefCell<DefaultTerminal>>, message: &str) {
+    let wait = WaitForKey::new(Text::from(message.to_string()));
+    let _ = term.borrow_mut().draw(|f| wait.render(f, f.area()));
+}
+
+// who won a free-for-all and when everyone else went down
+fn standings(result: &FreeForAllResult) -> String {
+    let mut lines = vec![
+        format!("{} wins the free-for-all!", result.names[result.winner]),
+        String::new(),
+    ];
+    for (place, player) in result.standings().into_iter().enumerate() {
+        let fired = result
+            .shots
+            .iter()
+            .filter(|shot| shot.shooter == player)
+            .count();
+        lines.push(format!(
+            "{}. {}, {fired} shots",
+            place + 1,
+            result.names[player]
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!("seed {}", result.seed));
+    lines.push(String::from("press any key"));
+    lines.join("\n")
+}
+
+// parses board sizes written as 15x15
+fn parse_size(size: &str) -> Option<(usize, usize)> {
+    let (width, height) = size.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
 }